@@ -0,0 +1,128 @@
+use codec::Decode;
+use frame_metadata::{v14::RuntimeMetadataV14, RuntimeMetadata, RuntimeMetadataPrefixed};
+use scale_info::{form::PortableForm, TypeDef, Variant};
+use std::{env, fmt::Write, fs, path::Path};
+
+// Generates a runtime module for every metadata file in `artifacts/`. The module
+// itself is expanded by the `tfchain_runtime!` macro in `src/runtimes/runtime.rs`,
+// this only lists the networks and detects the shape of their twin calls and whether
+// they support node power management.
+fn main() {
+    println!("cargo:rerun-if-changed=artifacts");
+
+    let mut runtimes = vec![];
+    for entry in fs::read_dir("artifacts").expect("failed to read artifacts directory") {
+        let path = entry.expect("failed to read artifact").path();
+        if path.extension() != Some("scale".as_ref()) {
+            continue;
+        }
+
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .expect("invalid artifact name")
+            .to_string();
+        if name.is_empty()
+            || name.starts_with(|c: char| c.is_ascii_digit())
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            panic!("artifact name '{}' is not a valid module name", name);
+        }
+
+        println!("cargo:rerun-if-changed={}", path.display());
+        let metadata = metadata(&path);
+        runtimes.push((name, twin_shape(&metadata), power_support(&metadata)));
+    }
+    runtimes.sort();
+
+    let mut out = String::new();
+    for (name, twin, power) in &runtimes {
+        writeln!(
+            out,
+            "tfchain_runtime!({}, {}, \"artifacts/{}.scale\", {}, {});",
+            name,
+            variant(name),
+            name,
+            twin,
+            power
+        )
+        .unwrap();
+    }
+
+    let list: Vec<String> = runtimes
+        .iter()
+        .map(|(name, _, _)| format!("{}: {}", name, variant(name)))
+        .collect();
+    writeln!(out, "runtimes!({});", list.join(", ")).unwrap();
+
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("runtimes.rs");
+    fs::write(dest, out).expect("failed to write runtimes");
+}
+
+// `qa_net` becomes `QaNet`
+fn variant(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn metadata(path: &Path) -> RuntimeMetadataV14 {
+    let bytes = fs::read(path).expect("failed to read metadata");
+    let metadata = RuntimeMetadataPrefixed::decode(&mut bytes.as_slice())
+        .unwrap_or_else(|_| panic!("invalid metadata in {}", path.display()));
+    match metadata.1 {
+        RuntimeMetadata::V14(metadata) => metadata,
+        _ => panic!("metadata in {} is not v14", path.display()),
+    }
+}
+
+fn tfgrid_call<'a>(
+    metadata: &'a RuntimeMetadataV14,
+    name: &str,
+) -> Option<&'a Variant<PortableForm>> {
+    let calls = metadata
+        .pallets
+        .iter()
+        .find(|p| p.name == "TfgridModule")
+        .and_then(|p| p.calls.as_ref())
+        .and_then(|calls| metadata.types.resolve(calls.ty.id()));
+
+    match calls.map(|ty| ty.type_def()) {
+        Some(TypeDef::Variant(variant)) => variant.variants().iter().find(|v| v.name() == name),
+        _ => None,
+    }
+}
+
+// older runtimes create twins with an `ip`, newer ones with a `relay` and `pk`
+fn twin_shape(metadata: &RuntimeMetadataV14) -> &'static str {
+    let has_relay = tfgrid_call(metadata, "create_twin")
+        .map(|v| {
+            v.fields()
+                .iter()
+                .any(|f| f.name().is_some_and(|n| n == "relay"))
+        })
+        .unwrap_or(false);
+
+    if has_relay {
+        "relay"
+    } else {
+        "ip"
+    }
+}
+
+// only newer runtimes let nodes report their power state and farmers set its target
+fn power_support(metadata: &RuntimeMetadataV14) -> &'static str {
+    if tfgrid_call(metadata, "change_power_target").is_some() {
+        "power"
+    } else {
+        "nopower"
+    }
+}
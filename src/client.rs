@@ -1,36 +1,44 @@
-use crate::runtimes::{devnet, local, mainnet, testnet, types};
+use crate::error::{Error, TfgridError};
+use crate::http::{is_http, HttpRpc};
+use crate::nonce::{is_nonce_error, NonceManager};
+use crate::proof::{self, Proven, ProvenEntry, StorageProof};
+use crate::retry::RetryPolicy;
+pub use crate::runtimes::Runtime;
+use crate::runtimes::{types, TfchainRuntime};
+use crate::signer::TfchainSigner;
+use crate::tx::{
+    fee_paid, DryRun, EncodedCall, ExtrinsicParamsBuilderOf, ExtrinsicParamsOf, FeeEstimate,
+    RuntimeDispatchInfo, SignerPayload, TxOptions, TxReceipt, WaitFor,
+};
+use crate::upgrade::{RuntimeUpgrade, Watcher};
+use crate::validate;
+pub use bip39::Mnemonic;
+use bip39::{Language, MnemonicType};
+use std::ops::Range;
 use std::str::FromStr;
+use std::sync::Arc;
 use subxt::{
     ext::{
-        sp_core::{crypto::SecretStringError, ed25519, sr25519, Pair},
-        sp_runtime::AccountId32,
+        codec::{Decode, Encode},
+        frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed, META_RESERVED},
+        sp_core::{
+            crypto::{DeriveJunction, SecretStringError, Ss58AddressFormat, Ss58Codec},
+            ed25519, sr25519, Bytes, Pair,
+        },
+        sp_runtime::{generic::Era, AccountId32},
+    },
+    rpc::{rpc_params, RpcClientT},
+    tx::{
+        ExtrinsicParams, PairSigner, PlainTip, Signer, SubmittableExtrinsic, TxPayload, TxProgress,
     },
-    tx::{PairSigner, Signer},
-    Error, OnlineClient, PolkadotConfig,
+    OnlineClient, PolkadotConfig,
+};
+use tokio::sync::broadcast;
+pub use types::{
+    BlockNumber, Contract, Entity, EntityProof, FarmPublicIPInput, Hash, Interface, Location,
+    PowerTarget, PublicConfig, Resources, SystemAccountInfo, TfgridFarm, TfgridNode, Twin,
+    TwinSpec, UptimeReport,
 };
-pub use types::{BlockNumber, Contract, Hash, SystemAccountInfo, TfgridFarm, TfgridNode, Twin};
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Runtime {
-    Local,
-    Devnet,
-    Testnet,
-    Mainnet,
-}
-
-impl FromStr for Runtime {
-    type Err = &'static str;
-
-    fn from_str(v: &str) -> Result<Self, Self::Err> {
-        match v {
-            "local" => Ok(Self::Local),
-            "devnet" => Ok(Self::Devnet),
-            "mainnet" => Ok(Self::Mainnet),
-            "testnet" => Ok(Self::Testnet),
-            _ => Err("unknown runtime"),
-        }
-    }
-}
 
 #[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum KeyType {
@@ -50,6 +58,7 @@ impl FromStr for KeyType {
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum KeyPair {
     Sr25519(sr25519::Pair),
     Ed25519(ed25519::Pair),
@@ -78,14 +87,167 @@ impl KeyPair {
         Ok(pair)
     }
 
+    // generate a new key pair from a random BIP-39 mnemonic of 12 or 24 words. The
+    // mnemonic is returned so it can be stored and imported later with `from_phrase`
+    pub fn generate(k: KeyType, words: usize) -> Result<(Self, Mnemonic), SecretStringError> {
+        let mnemonic_type = match words {
+            12 => MnemonicType::Words12,
+            24 => MnemonicType::Words24,
+            _ => return Err(SecretStringError::InvalidPhrase),
+        };
+        let mnemonic = Mnemonic::new(mnemonic_type, Language::English);
+
+        let pair = Self::from_phrase(k, mnemonic.phrase(), None)?;
+
+        Ok((pair, mnemonic))
+    }
+
+    // derive a key pair from a path of `//hard` and `/soft` junctions, for example
+    // `//farm//1`. Ed25519 keys only support hard junctions.
+    pub fn derive(&self, path: &str) -> Result<Self, SecretStringError> {
+        let path = parse_derivation_path(path)?;
+
+        let pair = match self {
+            Self::Sr25519(pair) => Self::Sr25519(
+                pair.derive(path.into_iter(), None)
+                    .map_err(|_| SecretStringError::InvalidPath)?
+                    .0,
+            ),
+            Self::Ed25519(pair) => Self::Ed25519(
+                pair.derive(path.into_iter(), None)
+                    .map_err(|_| SecretStringError::InvalidPath)?
+                    .0,
+            ),
+        };
+
+        Ok(pair)
+    }
+
+    // derive the key pairs `{base}//{index}` for every index in the range
+    pub fn derive_range(
+        &self,
+        base: &str,
+        indexes: Range<u32>,
+    ) -> Result<Vec<(u32, Self)>, SecretStringError> {
+        indexes
+            .map(|index| Ok((index, self.derive(&format!("{}//{}", base, index))?)))
+            .collect()
+    }
+
+    pub fn key_type(&self) -> KeyType {
+        match self {
+            Self::Sr25519(_) => KeyType::Sr25519,
+            Self::Ed25519(_) => KeyType::Ed25519,
+        }
+    }
+
+    // the raw public key
+    pub fn public(&self) -> [u8; 32] {
+        match self {
+            Self::Sr25519(pair) => pair.public().0,
+            Self::Ed25519(pair) => pair.public().0,
+        }
+    }
+
+    pub fn account_id(&self) -> AccountId32 {
+        AccountId32::from(self.public())
+    }
+
+    // the SS58 address of the account for the given network prefix, tfchain uses 42
+    pub fn to_ss58(&self, prefix: u16) -> String {
+        self.account_id()
+            .to_ss58check_with_version(Ss58AddressFormat::custom(prefix))
+    }
+
+    // sign the message as is, both key types produce 64 bytes signatures
+    pub fn sign(&self, msg: &[u8]) -> [u8; 64] {
+        match self {
+            Self::Sr25519(pair) => pair.sign(msg).0,
+            Self::Ed25519(pair) => pair.sign(msg).0,
+        }
+    }
+
+    // sign the message wrapped in `<Bytes>..</Bytes>`, the same way polkadot-js
+    // `signRaw` does. Use `verify_wrapped` to verify the signature.
+    pub fn sign_wrapped(&self, msg: &[u8]) -> [u8; 64] {
+        self.sign(&wrap_bytes(msg))
+    }
+
     pub fn signer(&self) -> Box<dyn Signer<PolkadotConfig> + Send + Sync> {
         match self {
-            Self::Ed25519(pair) => Box::new(PairSigner::new(pair.clone())),
+            Self::Ed25519(pair) => Box::new(PairSigner::new(*pair)),
             Self::Sr25519(pair) => Box::new(PairSigner::new(pair.clone())),
         }
     }
 }
 
+// parses a derivation path as accepted by `Pair::from_string`, without the
+// phrase and password
+fn parse_derivation_path(path: &str) -> Result<Vec<DeriveJunction>, SecretStringError> {
+    let mut junctions = vec![];
+    let mut rest = path;
+    while !rest.is_empty() {
+        let junction = rest
+            .strip_prefix('/')
+            .ok_or(SecretStringError::InvalidPath)?;
+        let (hard, junction) = match junction.strip_prefix('/') {
+            Some(junction) => (true, junction),
+            None => (false, junction),
+        };
+
+        let end = junction.find('/').unwrap_or(junction.len());
+        let (code, next) = junction.split_at(end);
+        if code.is_empty() {
+            return Err(SecretStringError::InvalidPath);
+        }
+
+        let junction = DeriveJunction::from(code);
+        junctions.push(if hard { junction.harden() } else { junction });
+        rest = next;
+    }
+
+    Ok(junctions)
+}
+
+const BYTES_PREFIX: &[u8] = b"<Bytes>";
+const BYTES_SUFFIX: &[u8] = b"</Bytes>";
+
+// wrap the message in `<Bytes>..</Bytes>` unless it is already wrapped, this is
+// what polkadot-js extensions sign for raw payloads so they can't be mistaken
+// for extrinsics
+pub fn wrap_bytes(msg: &[u8]) -> Vec<u8> {
+    if msg.starts_with(BYTES_PREFIX) && msg.ends_with(BYTES_SUFFIX) {
+        return msg.to_vec();
+    }
+
+    [BYTES_PREFIX, msg, BYTES_SUFFIX].concat()
+}
+
+// verify a signature of the message made by the given public key. Invalid keys
+// or signatures are reported as a failed verification
+pub fn verify(k: KeyType, public: &[u8], msg: &[u8], sig: &[u8]) -> bool {
+    let public: [u8; 32] = match public.try_into() {
+        Ok(public) => public,
+        Err(_) => return false,
+    };
+
+    match k {
+        KeyType::Sr25519 => match sr25519::Signature::from_slice(sig) {
+            Some(sig) => sr25519::Pair::verify(&sig, msg, &sr25519::Public::from_raw(public)),
+            None => false,
+        },
+        KeyType::Ed25519 => match ed25519::Signature::from_slice(sig) {
+            Some(sig) => ed25519::Pair::verify(&sig, msg, &ed25519::Public::from_raw(public)),
+            None => false,
+        },
+    }
+}
+
+// verify a signature made with polkadot-js `signRaw` or `KeyPair::sign_wrapped`
+pub fn verify_wrapped(k: KeyType, public: &[u8], msg: &[u8], sig: &[u8]) -> bool {
+    verify(k, public, &wrap_bytes(msg), sig)
+}
+
 impl From<sr25519::Pair> for KeyPair {
     fn from(value: sr25519::Pair) -> Self {
         Self::Sr25519(value)
@@ -102,60 +264,1097 @@ impl From<ed25519::Pair> for KeyPair {
 pub struct Client {
     pub runtime: Runtime,
     pub api: OnlineClient<PolkadotConfig>,
+    // no watcher is running over http, it needs a subscription
+    watcher: Option<Arc<Watcher>>,
+    nonces: Arc<NonceManager>,
+    retry: RetryPolicy,
 }
 
-macro_rules! call {
-    ($self:ident, $name:ident, $($arg:expr),+) => (
-        match $self.runtime {
-            Runtime::Local => local::$name($self, $($arg),+).await,
-            Runtime::Devnet => devnet::$name($self, $($arg),+).await,
-            Runtime::Testnet => testnet::$name($self, $($arg),+).await,
-            Runtime::Mainnet => mainnet::$name($self, $($arg),+).await,
+impl Client {
+    // Connects to the node and detects the runtime it is running from its metadata.
+    // Fails with `Error::UnsupportedRuntime` if none of the bundled runtimes match,
+    // use `Client::new` with `Runtime::Dynamic` to talk to such nodes.
+    // An http(s) url selects the http transport, which suits short lived jobs doing
+    // reads. Everything needing a subscription, such as waiting for an extrinsic to be
+    // included, fails with `Error::SubscriptionUnsupported` over it.
+    pub async fn connect<U: AsRef<str>>(url: U) -> Result<Client, Error> {
+        let url = url.as_ref();
+        if is_http(url) {
+            let api = OnlineClient::<PolkadotConfig>::from_rpc_client(Arc::new(HttpRpc::new(url)?))
+                .await?;
+
+            return Client::detect(api, false);
         }
-    )
-}
 
-impl Client {
+        let api = OnlineClient::<PolkadotConfig>::from_url(url).await?;
+
+        Client::detect(api, true)
+    }
+
+    // Connects through the rpc client and detects the runtime like `connect`. Use it
+    // with a `ReconnectingRpc` to fail over between endpoints, or with a `LightRpc` to
+    // check what is read against storage proofs instead of trusting a node.
+    pub async fn from_rpc<R: RpcClientT>(rpc: R) -> Result<Client, Error> {
+        let api = OnlineClient::<PolkadotConfig>::from_rpc_client(Arc::new(rpc)).await?;
+
+        Client::detect(api, true)
+    }
+
+    // Connects through the rpc client using the given runtime, skipping runtime detection
+    pub async fn from_rpc_with_runtime<R: RpcClientT>(
+        rpc: R,
+        runtime: Runtime,
+    ) -> Result<Client, Error> {
+        let api = OnlineClient::<PolkadotConfig>::from_rpc_client(Arc::new(rpc)).await?;
+
+        Ok(Client::with_runtime(api, runtime, true))
+    }
+
+    fn detect(api: OnlineClient<PolkadotConfig>, watch: bool) -> Result<Client, Error> {
+        let runtime = Runtime::detect(&api).ok_or_else(|| Error::UnsupportedRuntime {
+            spec_version: api.runtime_version().spec_version,
+            genesis_hash: api.genesis_hash(),
+        })?;
+
+        Ok(Client::with_runtime(api, runtime, watch))
+    }
+
+    // Connects to the node using the given runtime, skipping runtime detection.
+    // Use `connect` unless you need to override the detected runtime. Like `connect`
+    // an http(s) url selects the http transport.
     pub async fn new<U: AsRef<str>>(url: U, runtime: Runtime) -> Result<Client, Error> {
+        let url = url.as_ref();
+        if is_http(url) {
+            let api = OnlineClient::<PolkadotConfig>::from_rpc_client(Arc::new(HttpRpc::new(url)?))
+                .await?;
+
+            return Ok(Client::with_runtime(api, runtime, false));
+        }
+
         let api = OnlineClient::<PolkadotConfig>::from_url(url).await?;
 
-        Ok(Client { api, runtime })
+        Ok(Client::with_runtime(api, runtime, true))
+    }
+
+    fn with_runtime(api: OnlineClient<PolkadotConfig>, runtime: Runtime, watch: bool) -> Client {
+        let watcher = watch.then(|| Arc::new(Watcher::new(api.clone(), runtime)));
+
+        Client {
+            runtime,
+            api,
+            watcher,
+            nonces: Arc::new(NonceManager::new()),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    // Sets how requests failing with a transient error are retried. Storage reads are
+    // retried with the default policy unless it is replaced.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    // Subscribes to the runtime upgrades of the node. Every upgrade is checked against
+    // the bundled metadata, once an incompatible upgrade is seen all calls on the client
+    // fail with `Error::IncompatibleRuntime`. While the subscription is lost and cannot be
+    // re-established calls fail with `Error::UpgradesNotWatched`. Upgrades are not
+    // watched over http.
+    pub fn runtime_upgrades(&self) -> Result<broadcast::Receiver<RuntimeUpgrade>, Error> {
+        match &self.watcher {
+            Some(watcher) => Ok(watcher.subscribe()),
+            None => Err(Error::SubscriptionUnsupported(
+                "state_subscribeRuntimeVersion".into(),
+            )),
+        }
+    }
+
+    // Returns the implementation of the client runtime, failing if the node was
+    // upgraded to a runtime that is not compatible with it
+    fn implementation(&self) -> Result<&'static dyn TfchainRuntime, Error> {
+        self.ensure_compatible()?;
+
+        Ok(self.runtime.implementation())
+    }
+
+    // Fails if the node was upgraded to a runtime that does not match the generated code,
+    // or if the upgrades cannot be watched anymore
+    pub fn ensure_compatible(&self) -> Result<(), Error> {
+        if let Some(reason) = self.watcher.as_ref().and_then(|watcher| watcher.lost()) {
+            return Err(Error::UpgradesNotWatched(reason));
+        }
+
+        match self.watcher.as_ref().and_then(|watcher| watcher.last()) {
+            Some(upgrade) if !upgrade.is_compatible() => Err(Error::IncompatibleRuntime {
+                spec_version: upgrade.spec_version,
+                pallets: upgrade.pallets,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    // Builds the payload the account has to sign to submit the call, using the
+    // next nonce of the account. The signature is attached with `SignerPayload::into_signed`
+    // and the extrinsic submitted with `submit_raw`.
+    pub async fn payload<Call: TxPayload>(
+        &self,
+        call: &Call,
+        account: &AccountId32,
+        opts: &TxOptions,
+    ) -> Result<SignerPayload, Error> {
+        let nonce = self.api.rpc().system_account_next_index(account).await?;
+        let other = self.params(opts).await?;
+
+        self.payload_with_nonce(call, nonce, other)
+    }
+
+    // the tip and era of the extrinsic, mortal extrinsics are checkpointed at the
+    // last finalized block
+    async fn params(&self, opts: &TxOptions) -> Result<ExtrinsicParamsBuilderOf, Error> {
+        let params = ExtrinsicParamsBuilderOf::new().tip(PlainTip::new(opts.tip));
+
+        let period = match opts.mortality {
+            Some(period) => period,
+            None => return Ok(params),
+        };
+
+        let hash = self.api.rpc().finalized_head().await?;
+        let header = self.api.rpc().header(Some(hash)).await?.ok_or_else(|| {
+            subxt::Error::Other(format!("header of finalized block {:?} not found", hash))
+        })?;
+
+        Ok(params.era(Era::mortal(period, header.number.into()), hash))
+    }
+
+    fn payload_with_nonce<Call: TxPayload>(
+        &self,
+        call: &Call,
+        nonce: u32,
+        other: ExtrinsicParamsBuilderOf,
+    ) -> Result<SignerPayload, Error> {
+        self.api.tx().validate(call)?;
+        let call_data = self.api.tx().call_data(call)?;
+
+        let runtime = self.api.runtime_version();
+        let params = ExtrinsicParamsOf::new(
+            runtime.spec_version,
+            runtime.transaction_version,
+            nonce,
+            self.api.genesis_hash(),
+            other,
+        );
+
+        Ok(SignerPayload::new(call_data, params))
+    }
+
+    // Signs the call with the signer and submits it, the returned progress is used to
+    // wait for the extrinsic to be included in a block. Nonces are allocated by the
+    // client nonce manager so calls from the same account can be submitted concurrently,
+    // an extrinsic rejected because of its nonce is signed again after a resync.
+    pub async fn sign_and_submit<Call: TxPayload>(
+        &self,
+        call: &Call,
+        signer: &dyn TfchainSigner,
+        opts: &TxOptions,
+    ) -> Result<TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>, Error> {
+        let account = signer.account_id();
+        let other = self.params(opts).await?;
+        let mut resynced = false;
+
+        loop {
+            let nonce = self.nonces.next(&self.api, &account).await?;
+            let result = self
+                .submit_with_nonce(call, signer, &account, nonce, other)
+                .await;
+            let retry = matches!(&result, Err(err) if is_nonce_error(err)) && !resynced;
+            // the nonce was not used, the next one is read from the node
+            self.nonces.done(&account, result.is_err() && !retry).await;
+
+            if !retry {
+                return result;
+            }
+            self.nonces.resync(&self.api, &account).await?;
+            resynced = true;
+        }
+    }
+
+    async fn submit_with_nonce<Call: TxPayload>(
+        &self,
+        call: &Call,
+        signer: &dyn TfchainSigner,
+        account: &AccountId32,
+        nonce: u32,
+        other: ExtrinsicParamsBuilderOf,
+    ) -> Result<TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>, Error> {
+        let extrinsic = self
+            .sign_with_nonce(call, signer, account, nonce, other)
+            .await?;
+
+        Ok(
+            SubmittableExtrinsic::from_bytes(self.api.clone(), extrinsic)
+                .submit_and_watch()
+                .await?,
+        )
+    }
+
+    async fn sign_with_nonce<Call: TxPayload>(
+        &self,
+        call: &Call,
+        signer: &dyn TfchainSigner,
+        account: &AccountId32,
+        nonce: u32,
+        other: ExtrinsicParamsBuilderOf,
+    ) -> Result<Vec<u8>, Error> {
+        let payload = self.payload_with_nonce(call, nonce, other)?;
+        let signature = signer.sign(&payload.encode()).await?;
+
+        Ok(payload.into_extrinsic(account.clone(), signature))
+    }
+
+    // Signs and submits the call, then waits for the extrinsic as set in the options.
+    // A failed extrinsic is reported as an error. Transient failures are retried as set
+    // in the client retry policy, see `resubmit`.
+    pub async fn submit<Call: TxPayload>(
+        &self,
+        call: &Call,
+        signer: &dyn TfchainSigner,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let account = signer.account_id();
+        // whether a nonce is allocated and not done, it is still set when the
+        // submission timed out
+        let mut allocated = false;
+        let submit = async {
+            let other = self.params(opts).await?;
+            let mut resynced = false;
+
+            loop {
+                let nonce = self.nonces.next(&self.api, &account).await?;
+                allocated = true;
+                let extrinsic = self
+                    .sign_with_nonce(call, signer, &account, nonce, other)
+                    .await?;
+
+                match self.submit_and_wait(&extrinsic, opts.wait_for).await {
+                    Err(err) if is_nonce_error(&err) && !resynced => {
+                        self.nonces.done(&account, false).await;
+                        allocated = false;
+                        self.nonces.resync(&self.api, &account).await?;
+                        resynced = true;
+                    }
+                    Err(err) => {
+                        return self
+                            .resubmit(err, &account, nonce, &extrinsic, opts.wait_for)
+                            .await
+                    }
+                    Ok(receipt) => return Ok(receipt),
+                }
+            }
+        };
+
+        let result = match opts.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, submit)
+                .await
+                .unwrap_or(Err(Error::Timeout(timeout))),
+            None => submit.await,
+        };
+
+        if allocated {
+            // the nonce may not have been used, the next one is read from the node
+            self.nonces.done(&account, result.is_err()).await;
+        }
+
+        result
+    }
+
+    // Sends a signed extrinsic again after a transient failure. It is only sent again
+    // when it is verifiably not included: its nonce is not used on chain and it is not
+    // waiting in the pool of the node. The extrinsic keeps its nonce so it can never be
+    // executed twice.
+    async fn resubmit(
+        &self,
+        mut err: Error,
+        account: &AccountId32,
+        nonce: u32,
+        extrinsic: &[u8],
+        wait_for: WaitFor,
+    ) -> Result<TxReceipt, Error> {
+        let mut attempt = 1;
+
+        while self.retry.should_retry(&err, attempt) {
+            tokio::time::sleep(self.retry.backoff(attempt)).await;
+            attempt += 1;
+
+            if !matches!(
+                self.is_not_included(account, nonce, extrinsic).await,
+                Ok(true)
+            ) {
+                return Err(err);
+            }
+
+            match self.submit_and_wait(extrinsic, wait_for).await {
+                Ok(receipt) => return Ok(receipt),
+                Err(e) => err = e,
+            }
+        }
+
+        Err(err)
+    }
+
+    async fn is_not_included(
+        &self,
+        account: &AccountId32,
+        nonce: u32,
+        extrinsic: &[u8],
+    ) -> Result<bool, Error> {
+        let next = self.retry.run(|| self.account_nonce(account)).await?;
+        if next > nonce {
+            return Ok(false);
+        }
+
+        let pending: Vec<Bytes> = self
+            .retry
+            .run(|| async {
+                Ok(self
+                    .api
+                    .rpc()
+                    .request("author_pendingExtrinsics", rpc_params![])
+                    .await?)
+            })
+            .await?;
+
+        Ok(!pending.iter().any(|pending| pending.0 == extrinsic))
+    }
+
+    // the next nonce of the account on chain, extrinsics in the pool are not counted
+    async fn account_nonce(&self, account: &AccountId32) -> Result<u32, Error> {
+        let nonce: Bytes = self
+            .api
+            .rpc()
+            .request(
+                "state_call",
+                rpc_params!["AccountNonceApi_account_nonce", Bytes(account.encode())],
+            )
+            .await?;
+
+        Ok(u32::decode(&mut &nonce[..]).map_err(subxt::Error::from)?)
+    }
+
+    async fn submit_and_wait(
+        &self,
+        extrinsic: &[u8],
+        wait_for: WaitFor,
+    ) -> Result<TxReceipt, Error> {
+        let extrinsic = SubmittableExtrinsic::from_bytes(self.api.clone(), extrinsic.to_vec());
+
+        // submitting without watching needs no subscription, so it works over http
+        if wait_for == WaitFor::Broadcast {
+            return Ok(TxReceipt::broadcast(extrinsic.submit().await?));
+        }
+
+        self.wait(extrinsic.submit_and_watch().await?, wait_for)
+            .await
+    }
+
+    async fn wait(
+        &self,
+        progress: TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+        wait_for: WaitFor,
+    ) -> Result<TxReceipt, Error> {
+        let extrinsic_hash = progress.extrinsic_hash();
+        let (events, finalized) = match wait_for {
+            WaitFor::Broadcast => return Ok(TxReceipt::broadcast(extrinsic_hash)),
+            WaitFor::InBlock => (
+                progress
+                    .wait_for_in_block()
+                    .await?
+                    .wait_for_success()
+                    .await?,
+                false,
+            ),
+            WaitFor::Finalized => (progress.wait_for_finalized_success().await?, true),
+        };
+
+        let block_hash = events.block_hash();
+        let block_number = self
+            .api
+            .rpc()
+            .header(Some(block_hash))
+            .await?
+            .map(|header| header.number);
+        let extrinsic_index = events.extrinsic_index();
+        let events = events.iter().collect::<Result<Vec<_>, _>>()?;
+
+        Ok(TxReceipt {
+            extrinsic_hash,
+            block_hash: Some(block_hash),
+            block_number,
+            extrinsic_index: Some(extrinsic_index),
+            fee: fee_paid(&events),
+            events,
+            finalized,
+        })
+    }
+
+    // Reads the next nonce of the account from the node, use it after an extrinsic
+    // submitted by the client was dropped or found invalid
+    pub async fn resync_nonce(&self, account: &AccountId32) -> Result<u32, Error> {
+        self.nonces.resync(&self.api, account).await
+    }
+
+    // Estimates the fee the signer would pay to submit the call. Signatures do not
+    // change the fee so the signer is not asked to sign.
+    pub async fn estimate_fee<Call: TxPayload>(
+        &self,
+        call: &Call,
+        signer: &dyn TfchainSigner,
+    ) -> Result<FeeEstimate, Error> {
+        let account = signer.account_id();
+        let payload = self.payload(call, &account, &TxOptions::default()).await?;
+        let extrinsic =
+            payload.into_extrinsic(account, sr25519::Signature::from_raw([0; 64]).into());
+
+        let info: RuntimeDispatchInfo = self
+            .api
+            .rpc()
+            .request(
+                "payment_queryInfo",
+                rpc_params![format!("0x{}", hex::encode(extrinsic))],
+            )
+            .await?;
+
+        info.try_into()
+    }
+
+    // Applies the call signed by the signer on top of the best block without submitting
+    // it, to find out if it would fail. The node must expose unsafe RPC methods.
+    pub async fn dry_run<Call: TxPayload>(
+        &self,
+        call: &Call,
+        signer: &dyn TfchainSigner,
+    ) -> Result<DryRun, Error> {
+        let account = signer.account_id();
+        let payload = self.payload(call, &account, &TxOptions::default()).await?;
+        let signature = signer.sign(&payload.encode()).await?;
+        let extrinsic = payload.into_extrinsic(account, signature);
+
+        let result: Bytes = self
+            .api
+            .rpc()
+            .request(
+                "system_dryRun",
+                rpc_params![format!("0x{}", hex::encode(extrinsic))],
+            )
+            .await?;
+
+        DryRun::decode(&result, &self.api.metadata())
+    }
+
+    // Submits an extrinsic that was signed elsewhere, for example with an
+    // `OfflineBuilder`, and waits for it as set in the options. The mortality and
+    // tip of the options are ignored, they are part of the signed extrinsic.
+    pub async fn submit_raw<B: AsRef<[u8]>>(
+        &self,
+        extrinsic: B,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let submit = self.submit_and_wait(extrinsic.as_ref(), opts.wait_for);
+
+        match opts.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, submit)
+                .await
+                .map_err(|_| Error::Timeout(timeout))?,
+            None => submit.await,
+        }
+    }
+
+    // Returns the encoded metadata of the node, it is saved to build extrinsics offline
+    pub fn metadata_bytes(&self) -> Vec<u8> {
+        let metadata = self.api.metadata().runtime_metadata().clone();
+
+        RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V14(metadata)).encode()
+    }
+
+    // The calls submitted by the methods below, they are passed to `estimate_fee`
+    // or `dry_run` to check a call before submitting it
+    pub fn create_twin_call(&self, spec: TwinSpec) -> Result<EncodedCall, Error> {
+        self.implementation()?.create_twin_call(self, spec)
+    }
+
+    pub fn update_twin_call(&self, spec: TwinSpec) -> Result<EncodedCall, Error> {
+        self.implementation()?.update_twin_call(self, spec)
+    }
+
+    pub fn sign_terms_and_conditions_call(
+        &self,
+        document_link: String,
+        document_hash: String,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?
+            .sign_terms_and_conditions_call(self, document_link, document_hash)
+    }
+
+    pub fn create_farm_call(
+        &self,
+        name: String,
+        public_ips: Vec<FarmPublicIPInput>,
+    ) -> Result<EncodedCall, Error> {
+        validate::farm_public_ips(&public_ips)?;
+
+        self.implementation()?
+            .create_farm_call(self, name, public_ips)
+    }
+
+    pub fn update_farm_call(&self, id: u32, name: String) -> Result<EncodedCall, Error> {
+        self.implementation()?.update_farm_call(self, id, name)
+    }
+
+    pub fn add_farm_ip_call(
+        &self,
+        id: u32,
+        ip: String,
+        gateway: String,
+    ) -> Result<EncodedCall, Error> {
+        validate::farm_public_ip(&ip, &gateway)?;
+
+        self.implementation()?
+            .add_farm_ip_call(self, id, ip, gateway)
+    }
+
+    pub fn remove_farm_ip_call(&self, id: u32, ip: String) -> Result<EncodedCall, Error> {
+        validate::public_ip(&ip)?;
+
+        self.implementation()?.remove_farm_ip_call(self, id, ip)
+    }
+
+    pub fn add_stellar_payout_v2address_call(
+        &self,
+        farm_id: u32,
+        stellar_address: String,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?
+            .add_stellar_payout_v2address_call(self, farm_id, stellar_address)
+    }
+
+    pub fn set_farm_dedicated_call(
+        &self,
+        farm_id: u32,
+        dedicated: bool,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?
+            .set_farm_dedicated_call(self, farm_id, dedicated)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_node_call(
+        &self,
+        farm_id: u32,
+        resources: Resources,
+        location: Location,
+        interfaces: Vec<Interface>,
+        secure_boot: bool,
+        virtualized: bool,
+        serial_number: Option<String>,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?.create_node_call(
+            self,
+            farm_id,
+            resources,
+            location,
+            interfaces,
+            secure_boot,
+            virtualized,
+            serial_number,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_node_call(
+        &self,
+        node_id: u32,
+        farm_id: u32,
+        resources: Resources,
+        location: Location,
+        interfaces: Vec<Interface>,
+        secure_boot: bool,
+        virtualized: bool,
+        serial_number: Option<String>,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?.update_node_call(
+            self,
+            node_id,
+            farm_id,
+            resources,
+            location,
+            interfaces,
+            secure_boot,
+            virtualized,
+            serial_number,
+        )
+    }
+
+    pub fn delete_node_call(&self, id: u32) -> Result<EncodedCall, Error> {
+        self.implementation()?.delete_node_call(self, id)
+    }
+
+    pub fn add_node_public_config_call(
+        &self,
+        farm_id: u32,
+        node_id: u32,
+        public_config: Option<PublicConfig>,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?
+            .add_node_public_config_call(self, farm_id, node_id, public_config)
+    }
+
+    pub fn report_uptime_call(&self, uptime: u64) -> Result<EncodedCall, Error> {
+        self.implementation()?.report_uptime_call(self, uptime)
+    }
+
+    pub fn change_power_state_call(&self, state: PowerTarget) -> Result<EncodedCall, Error> {
+        self.implementation()?.change_power_state_call(self, state)
+    }
+
+    pub fn change_power_target_call(
+        &self,
+        node_id: u32,
+        target: PowerTarget,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?
+            .change_power_target_call(self, node_id, target)
+    }
+
+    // The entity account signs its name, country and city to consent to the entity
+    // being created for it
+    pub fn create_entity_call(
+        &self,
+        entity: &KeyPair,
+        name: String,
+        country: String,
+        city: String,
+    ) -> Result<EncodedCall, Error> {
+        let message = [name.as_bytes(), country.as_bytes(), city.as_bytes()].concat();
+        let signature = entity_signature(entity, &message);
+
+        self.implementation()?.create_entity_call(
+            self,
+            entity.account_id(),
+            name,
+            country,
+            city,
+            signature,
+        )
+    }
+
+    pub fn update_entity_call(
+        &self,
+        name: String,
+        country: String,
+        city: String,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?
+            .update_entity_call(self, name, country, city)
     }
 
-    // Creates a twin and checks for success, twin ID is returned on success
+    pub fn delete_entity_call(&self) -> Result<EncodedCall, Error> {
+        self.implementation()?.delete_entity_call(self)
+    }
+
+    // The entity account signs the entity and twin IDs to consent to the twin being
+    // part of the entity
+    pub fn add_twin_entity_call(
+        &self,
+        twin_id: u32,
+        entity: &KeyPair,
+        entity_id: u32,
+    ) -> Result<EncodedCall, Error> {
+        let message = [entity_id.to_be_bytes(), twin_id.to_be_bytes()].concat();
+        let signature = entity_signature(entity, &message);
+
+        self.implementation()?
+            .add_twin_entity_call(self, twin_id, entity_id, signature)
+    }
+
+    pub fn delete_twin_entity_call(
+        &self,
+        twin_id: u32,
+        entity_id: u32,
+    ) -> Result<EncodedCall, Error> {
+        self.implementation()?
+            .delete_twin_entity_call(self, twin_id, entity_id)
+    }
+
+    // Creates a twin and checks for success, twin ID is returned on success. The twin
+    // ID is read from the events so this waits at least for the extrinsic to be in a block.
+    // Fails with `Error::FeatureUnsupportedOnRuntime` if the spec sets a field the
+    // runtime of the node does not have.
     pub async fn create_twin(
         &self,
-        kp: &KeyPair,
-        relay: Option<String>,
-        pk: Option<String>,
+        signer: &dyn TfchainSigner,
+        spec: TwinSpec,
+        opts: &TxOptions,
     ) -> Result<u32, Error> {
-        call!(self, create_twin, kp, relay, pk)
+        self.implementation()?
+            .create_twin(self, signer, spec, &in_block(opts))
+            .await
     }
 
-    // Updates a twin and checks for success, blockhash is returned on success
+    // Updates a twin and checks for success, the spec is checked as for `create_twin`
     pub async fn update_twin(
         &self,
-        kp: &KeyPair,
-        relay: Option<String>,
-        pk: Option<&[u8]>,
-    ) -> Result<Hash, Error> {
-        call!(self, update_twin, kp, relay, pk)
+        signer: &dyn TfchainSigner,
+        spec: TwinSpec,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        self.implementation()?
+            .update_twin(self, signer, spec, opts)
+            .await
     }
 
-    // Signs terms and condition and checks for success, blockhash is returned on success
+    // Signs terms and condition and checks for success
     pub async fn sign_terms_and_conditions(
         &self,
-        kp: &KeyPair,
+        signer: &dyn TfchainSigner,
         document_link: String,
         document_hash: String,
-    ) -> Result<Hash, Error> {
-        call!(
-            self,
-            sign_terms_and_conditions,
-            kp,
-            document_link,
-            document_hash
-        )
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        self.implementation()?
+            .sign_terms_and_conditions(self, signer, document_link, document_hash, opts)
+            .await
+    }
+
+    // The farm calls below return the farm as it is after the extrinsic. It is read from
+    // the events so they wait at least for the extrinsic to be in a block. IPs and
+    // gateways are checked before the extrinsic is signed.
+    pub async fn create_farm(
+        &self,
+        signer: &dyn TfchainSigner,
+        name: String,
+        public_ips: Vec<FarmPublicIPInput>,
+        opts: &TxOptions,
+    ) -> Result<TfgridFarm, Error> {
+        let call = self.create_farm_call(name, public_ips)?;
+
+        self.submit_farm(&call, signer, "FarmStored", opts).await
+    }
+
+    pub async fn update_farm(
+        &self,
+        signer: &dyn TfchainSigner,
+        id: u32,
+        name: String,
+        opts: &TxOptions,
+    ) -> Result<TfgridFarm, Error> {
+        let call = self.update_farm_call(id, name)?;
+
+        self.submit_farm(&call, signer, "FarmUpdated", opts).await
+    }
+
+    pub async fn add_farm_ip(
+        &self,
+        signer: &dyn TfchainSigner,
+        id: u32,
+        ip: String,
+        gateway: String,
+        opts: &TxOptions,
+    ) -> Result<TfgridFarm, Error> {
+        let call = self.add_farm_ip_call(id, ip, gateway)?;
+
+        self.submit_farm(&call, signer, "FarmUpdated", opts).await
+    }
+
+    pub async fn remove_farm_ip(
+        &self,
+        signer: &dyn TfchainSigner,
+        id: u32,
+        ip: String,
+        opts: &TxOptions,
+    ) -> Result<TfgridFarm, Error> {
+        let call = self.remove_farm_ip_call(id, ip)?;
+
+        self.submit_farm(&call, signer, "FarmUpdated", opts).await
+    }
+
+    // The payout address is not part of the farm and no farm event is emitted, the
+    // farm is read at the block the extrinsic was included in.
+    pub async fn add_stellar_payout_v2address(
+        &self,
+        signer: &dyn TfchainSigner,
+        farm_id: u32,
+        stellar_address: String,
+        opts: &TxOptions,
+    ) -> Result<TfgridFarm, Error> {
+        let call = self.add_stellar_payout_v2address_call(farm_id, stellar_address)?;
+        let receipt = self.submit(&call, signer, &in_block(opts)).await?;
+
+        self.get_farm_by_id(farm_id, receipt.block_hash)
+            .await?
+            .ok_or(Error::Tfgrid(TfgridError::FarmNotExists))
+    }
+
+    pub async fn set_farm_dedicated(
+        &self,
+        signer: &dyn TfchainSigner,
+        farm_id: u32,
+        dedicated: bool,
+        opts: &TxOptions,
+    ) -> Result<TfgridFarm, Error> {
+        let call = self.set_farm_dedicated_call(farm_id, dedicated)?;
+
+        self.submit_farm(&call, signer, "FarmUpdated", opts).await
+    }
+
+    async fn submit_farm(
+        &self,
+        call: &EncodedCall,
+        signer: &dyn TfchainSigner,
+        event: &'static str,
+        opts: &TxOptions,
+    ) -> Result<TfgridFarm, Error> {
+        let receipt = self.submit(call, signer, &in_block(opts)).await?;
+
+        self.implementation()?
+            .farm_event(self, &receipt)?
+            .ok_or(Error::EventNotFound {
+                pallet: TfgridError::PALLET,
+                event,
+            })
+    }
+
+    // The node is read from the `NodeStored` event, so this waits at least for the
+    // extrinsic to be in a block.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_node(
+        &self,
+        signer: &dyn TfchainSigner,
+        farm_id: u32,
+        resources: Resources,
+        location: Location,
+        interfaces: Vec<Interface>,
+        secure_boot: bool,
+        virtualized: bool,
+        serial_number: Option<String>,
+        opts: &TxOptions,
+    ) -> Result<TfgridNode, Error> {
+        let call = self.create_node_call(
+            farm_id,
+            resources,
+            location,
+            interfaces,
+            secure_boot,
+            virtualized,
+            serial_number,
+        )?;
+
+        self.submit_node(&call, signer, "NodeStored", opts).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_node(
+        &self,
+        signer: &dyn TfchainSigner,
+        node_id: u32,
+        farm_id: u32,
+        resources: Resources,
+        location: Location,
+        interfaces: Vec<Interface>,
+        secure_boot: bool,
+        virtualized: bool,
+        serial_number: Option<String>,
+        opts: &TxOptions,
+    ) -> Result<TfgridNode, Error> {
+        let call = self.update_node_call(
+            node_id,
+            farm_id,
+            resources,
+            location,
+            interfaces,
+            secure_boot,
+            virtualized,
+            serial_number,
+        )?;
+
+        self.submit_node(&call, signer, "NodeUpdated", opts).await
+    }
+
+    pub async fn delete_node(
+        &self,
+        signer: &dyn TfchainSigner,
+        id: u32,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let call = self.delete_node_call(id)?;
+
+        self.submit(&call, signer, opts).await
+    }
+
+    // Only the public config is emitted by the pallet, the node is read at the block
+    // the extrinsic was included in.
+    pub async fn add_node_public_config(
+        &self,
+        signer: &dyn TfchainSigner,
+        farm_id: u32,
+        node_id: u32,
+        public_config: Option<PublicConfig>,
+        opts: &TxOptions,
+    ) -> Result<TfgridNode, Error> {
+        let call = self.add_node_public_config_call(farm_id, node_id, public_config)?;
+        let receipt = self.submit(&call, signer, &in_block(opts)).await?;
+
+        self.get_node_by_id(node_id, receipt.block_hash)
+            .await?
+            .ok_or(Error::Tfgrid(TfgridError::NodeNotExists))
+    }
+
+    async fn submit_node(
+        &self,
+        call: &EncodedCall,
+        signer: &dyn TfchainSigner,
+        event: &'static str,
+        opts: &TxOptions,
+    ) -> Result<TfgridNode, Error> {
+        let receipt = self.submit(call, signer, &in_block(opts)).await?;
+
+        self.implementation()?
+            .node_event(self, &receipt)?
+            .ok_or(Error::EventNotFound {
+                pallet: TfgridError::PALLET,
+                event,
+            })
+    }
+
+    // Reports the uptime of the node of the signer twin, in seconds. The report is
+    // read from the events so this waits at least for the extrinsic to be in a block.
+    pub async fn report_uptime(
+        &self,
+        signer: &dyn TfchainSigner,
+        uptime: u64,
+        opts: &TxOptions,
+    ) -> Result<UptimeReport, Error> {
+        let call = self.report_uptime_call(uptime)?;
+        let receipt = self.submit(&call, signer, &in_block(opts)).await?;
+
+        self.implementation()?
+            .uptime_event(self, &receipt)?
+            .ok_or(Error::EventNotFound {
+                pallet: TfgridError::PALLET,
+                event: "NodeUptimeReported",
+            })
+    }
+
+    // The power calls fail with `Error::FeatureUnsupportedOnRuntime` on runtimes
+    // without node power management. The node of the signer twin reports it is going
+    // up or down with `change_power_state`, the farmer asks a node of their farm to
+    // go up or down with `change_power_target`.
+    pub async fn change_power_state(
+        &self,
+        signer: &dyn TfchainSigner,
+        state: PowerTarget,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let call = self.change_power_state_call(state)?;
+
+        self.submit(&call, signer, opts).await
+    }
+
+    pub async fn change_power_target(
+        &self,
+        signer: &dyn TfchainSigner,
+        node_id: u32,
+        target: PowerTarget,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let call = self.change_power_target_call(node_id, target)?;
+
+        self.submit(&call, signer, opts).await
+    }
+
+    // The entity calls below return the entity as it is after the extrinsic. It is
+    // read from the events so they wait at least for the extrinsic to be in a block.
+    pub async fn create_entity(
+        &self,
+        signer: &dyn TfchainSigner,
+        entity: &KeyPair,
+        name: String,
+        country: String,
+        city: String,
+        opts: &TxOptions,
+    ) -> Result<Entity, Error> {
+        let call = self.create_entity_call(entity, name, country, city)?;
+
+        self.submit_entity(&call, signer, "EntityStored", opts)
+            .await
+    }
+
+    // updates the entity of the signer account
+    pub async fn update_entity(
+        &self,
+        signer: &dyn TfchainSigner,
+        name: String,
+        country: String,
+        city: String,
+        opts: &TxOptions,
+    ) -> Result<Entity, Error> {
+        let call = self.update_entity_call(name, country, city)?;
+
+        self.submit_entity(&call, signer, "EntityUpdated", opts)
+            .await
+    }
+
+    // deletes the entity of the signer account
+    pub async fn delete_entity(
+        &self,
+        signer: &dyn TfchainSigner,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let call = self.delete_entity_call()?;
+
+        self.submit(&call, signer, opts).await
+    }
+
+    // adds the twin of the signer to the entity, `entity` is the key of the entity
+    // account
+    pub async fn add_twin_entity(
+        &self,
+        signer: &dyn TfchainSigner,
+        twin_id: u32,
+        entity: &KeyPair,
+        entity_id: u32,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let call = self.add_twin_entity_call(twin_id, entity, entity_id)?;
+
+        self.submit(&call, signer, opts).await
+    }
+
+    pub async fn delete_twin_entity(
+        &self,
+        signer: &dyn TfchainSigner,
+        twin_id: u32,
+        entity_id: u32,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let call = self.delete_twin_entity_call(twin_id, entity_id)?;
+
+        self.submit(&call, signer, opts).await
+    }
+
+    async fn submit_entity(
+        &self,
+        call: &EncodedCall,
+        signer: &dyn TfchainSigner,
+        event: &'static str,
+        opts: &TxOptions,
+    ) -> Result<Entity, Error> {
+        let receipt = self.submit(call, signer, &in_block(opts)).await?;
+
+        self.implementation()?
+            .entity_event(self, &receipt)?
+            .ok_or(Error::EventNotFound {
+                pallet: TfgridError::PALLET,
+                event,
+            })
     }
 
     pub async fn get_twin_by_id(
@@ -163,7 +1362,13 @@ impl Client {
         id: u32,
         at_block: Option<Hash>,
     ) -> Result<Option<Twin>, Error> {
-        call!(self, get_twin_by_id, id, at_block)
+        self.retry
+            .run(|| async {
+                self.implementation()?
+                    .get_twin_by_id(self, id, at_block)
+                    .await
+            })
+            .await
     }
 
     pub async fn get_twin_id_by_account(
@@ -171,7 +1376,60 @@ impl Client {
         account: AccountId32,
         at_block: Option<types::Hash>,
     ) -> Result<Option<u32>, Error> {
-        call!(self, get_twin_id_by_account, account, at_block)
+        self.retry
+            .run(|| async {
+                self.implementation()?
+                    .get_twin_id_by_account(self, account.clone(), at_block)
+                    .await
+            })
+            .await
+    }
+
+    // Finds which of the accounts derived as `{base}//{index}` from the root key owns
+    // the twin, returning its index and key pair
+    pub async fn find_derived_twin(
+        &self,
+        root: &KeyPair,
+        base: &str,
+        indexes: Range<u32>,
+        twin_id: u32,
+    ) -> Result<Option<(u32, KeyPair)>, Error> {
+        for index in indexes {
+            let kp = root.derive(&format!("{}//{}", base, index))?;
+            if self.get_twin_id_by_account(kp.account_id(), None).await? == Some(twin_id) {
+                return Ok(Some((index, kp)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_entity_by_id(
+        &self,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<Entity>, Error> {
+        self.retry
+            .run(|| async {
+                self.implementation()?
+                    .get_entity_by_id(self, id, at_block)
+                    .await
+            })
+            .await
+    }
+
+    pub async fn get_entity_id_by_name(
+        &self,
+        name: &str,
+        at_block: Option<Hash>,
+    ) -> Result<Option<u32>, Error> {
+        self.retry
+            .run(|| async {
+                self.implementation()?
+                    .get_entity_id_by_name(self, name.to_string(), at_block)
+                    .await
+            })
+            .await
     }
 
     pub async fn get_farm_by_id(
@@ -179,7 +1437,13 @@ impl Client {
         id: u32,
         at_block: Option<Hash>,
     ) -> Result<Option<TfgridFarm>, Error> {
-        call!(self, get_farm_by_id, id, at_block)
+        self.retry
+            .run(|| async {
+                self.implementation()?
+                    .get_farm_by_id(self, id, at_block)
+                    .await
+            })
+            .await
     }
 
     pub async fn get_node_by_id(
@@ -187,7 +1451,13 @@ impl Client {
         id: u32,
         at_block: Option<Hash>,
     ) -> Result<Option<TfgridNode>, Error> {
-        call!(self, get_node_by_id, id, at_block)
+        self.retry
+            .run(|| async {
+                self.implementation()?
+                    .get_node_by_id(self, id, at_block)
+                    .await
+            })
+            .await
     }
 
     pub async fn get_balance(
@@ -195,14 +1465,29 @@ impl Client {
         account: &AccountId32,
         at_block: Option<Hash>,
     ) -> Result<Option<SystemAccountInfo>, Error> {
-        call!(self, get_balance, account, at_block)
+        self.retry
+            .run(|| async {
+                self.implementation()?
+                    .get_balance(self, account, at_block)
+                    .await
+            })
+            .await
     }
 
     pub async fn get_block_hash(
         &self,
         block_number: Option<BlockNumber>,
     ) -> Result<Option<Hash>, Error> {
-        call!(self, get_block_hash, block_number)
+        // BlockNumber is not Clone, it is serialized by reference on every attempt
+        self.retry
+            .run(|| async {
+                Ok(self
+                    .api
+                    .rpc()
+                    .request("chain_getBlockHash", rpc_params![&block_number])
+                    .await?)
+            })
+            .await
     }
 
     pub async fn get_contract_by_id(
@@ -210,6 +1495,344 @@ impl Client {
         id: u64,
         at_block: Option<Hash>,
     ) -> Result<Option<Contract>, Error> {
-        call!(self, get_contract_by_id, id, at_block)
+        self.retry
+            .run(|| async {
+                self.implementation()?
+                    .get_contract_by_id(self, id, at_block)
+                    .await
+            })
+            .await
+    }
+
+    // The getters below also return the proof of the entry the value is decoded from,
+    // to be checked offline with `proof::verify_proof`. They read at the last finalized
+    // block unless a block is given. The power of a node is kept apart from the node
+    // and is not proven, the proven node is up.
+    pub async fn get_twin_by_id_with_proof(
+        &self,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Proven<Option<Twin>>, Error> {
+        self.with_proof(proof::TwinById(id), at_block).await
+    }
+
+    pub async fn get_twin_id_by_account_with_proof(
+        &self,
+        account: AccountId32,
+        at_block: Option<Hash>,
+    ) -> Result<Proven<Option<u32>>, Error> {
+        self.with_proof(proof::TwinIdByAccount(account), at_block)
+            .await
+    }
+
+    pub async fn get_farm_by_id_with_proof(
+        &self,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Proven<Option<TfgridFarm>>, Error> {
+        self.with_proof(proof::FarmById(id), at_block).await
+    }
+
+    pub async fn get_node_by_id_with_proof(
+        &self,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Proven<Option<TfgridNode>>, Error> {
+        self.with_proof(proof::NodeById(id), at_block).await
+    }
+
+    pub async fn get_balance_with_proof(
+        &self,
+        account: &AccountId32,
+        at_block: Option<Hash>,
+    ) -> Result<Proven<Option<SystemAccountInfo>>, Error> {
+        self.with_proof(proof::Balance(account.clone()), at_block)
+            .await
+    }
+
+    pub async fn get_contract_by_id_with_proof(
+        &self,
+        id: u64,
+        at_block: Option<Hash>,
+    ) -> Result<Proven<Option<Contract>>, Error> {
+        self.with_proof(proof::ContractById(id), at_block).await
+    }
+
+    // reads the proof of the entry at the block, the entry and its value are taken from
+    // the proof so they are the ones proven
+    async fn with_proof<E: ProvenEntry>(
+        &self,
+        entry: E,
+        at_block: Option<Hash>,
+    ) -> Result<Proven<E::Value>, Error> {
+        let metadata = self.api.metadata();
+        let key = entry.key(&metadata)?;
+
+        let block_hash = match at_block {
+            Some(hash) => hash,
+            None => {
+                self.retry
+                    .run(|| async { Ok(self.api.rpc().finalized_head().await?) })
+                    .await?
+            }
+        };
+
+        let header = self
+            .retry
+            .run(|| async { Ok(self.api.rpc().header(Some(block_hash)).await?) })
+            .await?
+            .ok_or(Error::BlockNotFound(block_hash))?;
+
+        let proof: Vec<Vec<u8>> = self
+            .retry
+            .run(|| async {
+                Ok(self
+                    .api
+                    .rpc()
+                    .read_proof([key.as_slice()], Some(block_hash))
+                    .await?)
+            })
+            .await?
+            .proof
+            .into_iter()
+            .map(|node| node.0)
+            .collect();
+
+        let raw = proof::verify_storage(&header.state_root, &key, &proof)?;
+
+        Ok(Proven {
+            value: E::decode(&metadata, raw.as_deref())?,
+            proof: StorageProof {
+                raw,
+                key,
+                block_hash,
+                state_root: header.state_root,
+                proof,
+            },
+        })
+    }
+}
+
+// calls returning data read from the events need the extrinsic to be in a block
+fn in_block(opts: &TxOptions) -> TxOptions {
+    let mut opts = opts.clone();
+    if opts.wait_for == WaitFor::Broadcast {
+        opts.wait_for = WaitFor::InBlock;
+    }
+    opts
+}
+
+// entity signatures are checked by the pallet against the hex encoding of the signature
+fn entity_signature(entity: &KeyPair, message: &[u8]) -> String {
+    hex::encode(entity.sign(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtimes::testing;
+    use crate::signer::MockSigner;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use subxt::{
+        error::RpcError,
+        rpc::{RawValue, RpcClientT, RpcFuture, RpcSubscription},
+    };
+
+    // the substrate dev phrase, `//Alice` is derived from it
+    const PHRASE: &str = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+
+    #[test]
+    fn wrap_bytes_wraps_once() {
+        assert_eq!(wrap_bytes(b"hello"), b"<Bytes>hello</Bytes>");
+        assert_eq!(wrap_bytes(b"<Bytes>hello</Bytes>"), b"<Bytes>hello</Bytes>");
+        assert_eq!(wrap_bytes(b""), b"<Bytes></Bytes>");
+        assert_eq!(wrap_bytes(b"<Bytes>hello"), b"<Bytes><Bytes>hello</Bytes>");
+    }
+
+    #[test]
+    fn verify_wrapped_signatures() {
+        for key_type in [KeyType::Sr25519, KeyType::Ed25519] {
+            let kp = KeyPair::from_phrase(key_type, PHRASE, None).unwrap();
+            let other = KeyPair::from_phrase(key_type, "//Bob", None).unwrap();
+            let sig = kp.sign_wrapped(b"message");
+
+            assert!(verify_wrapped(key_type, &kp.public(), b"message", &sig));
+            // the message can be given already wrapped
+            assert!(verify_wrapped(
+                key_type,
+                &kp.public(),
+                b"<Bytes>message</Bytes>",
+                &sig
+            ));
+            assert!(verify(
+                key_type,
+                &kp.public(),
+                b"<Bytes>message</Bytes>",
+                &sig
+            ));
+
+            assert!(!verify(key_type, &kp.public(), b"message", &sig));
+            assert!(!verify_wrapped(key_type, &kp.public(), b"other", &sig));
+            assert!(!verify_wrapped(key_type, &other.public(), b"message", &sig));
+            assert!(!verify_wrapped(
+                key_type,
+                &kp.public()[1..],
+                b"message",
+                &sig
+            ));
+            assert!(!verify_wrapped(
+                key_type,
+                &kp.public(),
+                b"message",
+                &sig[1..]
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_hard_and_soft_junctions() {
+        assert_eq!(parse_derivation_path("").unwrap(), vec![]);
+        assert_eq!(
+            parse_derivation_path("//farm/1//2").unwrap(),
+            vec![
+                DeriveJunction::hard("farm"),
+                DeriveJunction::soft(1u64),
+                DeriveJunction::hard(2u64),
+            ]
+        );
+        assert_eq!(
+            parse_derivation_path("/soft").unwrap(),
+            vec![DeriveJunction::soft("soft")]
+        );
+
+        for path in ["farm", "//", "/", "//farm/", "//farm///1"] {
+            assert!(
+                matches!(
+                    parse_derivation_path(path),
+                    Err(SecretStringError::InvalidPath)
+                ),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn derive_matches_phrase_paths() {
+        let sr25519 = KeyPair::from_phrase(KeyType::Sr25519, PHRASE, None).unwrap();
+        let ed25519 = KeyPair::from_phrase(KeyType::Ed25519, PHRASE, None).unwrap();
+
+        // the well known dev accounts
+        assert_eq!(
+            hex::encode(sr25519.derive("//Alice").unwrap().public()),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+        assert_eq!(
+            hex::encode(ed25519.derive("//Alice").unwrap().public()),
+            "88dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee"
+        );
+
+        for path in ["//farm//1", "//farm/1", "/soft//hard/0"] {
+            let expected =
+                KeyPair::from_phrase(KeyType::Sr25519, format!("{}{}", PHRASE, path), None)
+                    .unwrap();
+            assert_eq!(sr25519.derive(path).unwrap().public(), expected.public());
+        }
+
+        // ed25519 keys cannot be soft derived
+        assert!(ed25519.derive("//farm/1").is_err());
+
+        let derived = sr25519.derive_range("//farm", 2..4).unwrap();
+        assert_eq!(derived.len(), 2);
+        assert_eq!(derived[0].0, 2);
+        assert_eq!(
+            derived[1].1.public(),
+            sr25519.derive("//farm//3").unwrap().public()
+        );
+    }
+
+    // a node whose pool rejects the first extrinsic as outdated, another client
+    // having used the nonces up to `index`
+    struct StaleRpc {
+        index: AtomicU32,
+        submitted: AtomicU32,
+    }
+
+    impl RpcClientT for StaleRpc {
+        fn request_raw<'a>(
+            &'a self,
+            method: &'a str,
+            _params: Option<Box<RawValue>>,
+        ) -> RpcFuture<'a, Box<RawValue>> {
+            Box::pin(async move {
+                if let Some(value) = testing::client_response("devnet", method) {
+                    return Ok(RawValue::from_string(value.to_string()).expect("value is json"));
+                }
+
+                let value = match method {
+                    "system_accountNextIndex" => {
+                        serde_json::json!(self.index.load(Ordering::SeqCst))
+                    }
+                    "author_submitExtrinsic" => {
+                        if self.submitted.fetch_add(1, Ordering::SeqCst) == 0 {
+                            self.index.store(9, Ordering::SeqCst);
+                            return Err(RpcError::ClientError(
+                                "Invalid Transaction (1010): Transaction is outdated".into(),
+                            ));
+                        }
+                        serde_json::json!(format!("0x{}", "11".repeat(32)))
+                    }
+                    method => panic!("unexpected request {}", method),
+                };
+
+                Ok(RawValue::from_string(value.to_string()).expect("value is json"))
+            })
+        }
+
+        fn subscribe_raw<'a>(
+            &'a self,
+            _sub: &'a str,
+            _params: Option<Box<RawValue>>,
+            _unsub: &'a str,
+        ) -> RpcFuture<'a, RpcSubscription> {
+            unimplemented!("no subscriptions are made")
+        }
+    }
+
+    #[tokio::test]
+    async fn stale_nonces_are_resynced_on_submit() {
+        let rpc = StaleRpc {
+            index: AtomicU32::new(5),
+            submitted: AtomicU32::new(0),
+        };
+        let api = OnlineClient::<PolkadotConfig>::from_rpc_client(Arc::new(rpc))
+            .await
+            .unwrap();
+        let client = Client::with_runtime(api, Runtime::Devnet, false);
+        let signer = MockSigner::new(KeyPair::from_phrase(KeyType::Sr25519, PHRASE, None).unwrap());
+        let call = subxt::dynamic::tx(
+            "System",
+            "remark",
+            vec![subxt::dynamic::Value::from_bytes(b"hi")],
+        );
+        let opts = TxOptions {
+            wait_for: WaitFor::Broadcast,
+            ..Default::default()
+        };
+
+        client.submit(&call, &signer, &opts).await.unwrap();
+
+        // signed first with the local nonce, then with the nonce read from the node
+        let other = client.params(&opts).await.unwrap();
+        let payload = |nonce| {
+            client
+                .payload_with_nonce(&call, nonce, other)
+                .unwrap()
+                .encode()
+        };
+        assert_eq!(signer.signed(), vec![payload(5), payload(9)]);
+
+        let account = signer.account_id();
+        assert_eq!(client.nonces.next(&client.api, &account).await.unwrap(), 10);
     }
 }
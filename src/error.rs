@@ -0,0 +1,383 @@
+use crate::http::SubscriptionUnsupported;
+use crate::runtimes::types::Hash;
+use std::fmt;
+use std::time::Duration;
+use subxt::{
+    error::{DispatchError, ModuleError, RpcError},
+    events::StaticEvent,
+    ext::sp_core::crypto::SecretStringError,
+};
+
+// Error is the error type returned by all client operations. Dispatch errors
+// from the tfchain pallets are decoded into their typed variants so callers can
+// match on them directly.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    // transport level failure while talking to the node
+    #[error("rpc error: {0}")]
+    Rpc(RpcError),
+    // failure to decode data returned by the node
+    #[error("decoding error: {0}")]
+    Decode(subxt::Error),
+    #[error("tfgrid module error: {0}")]
+    Tfgrid(TfgridError),
+    #[error("smart contract module error: {0}")]
+    SmartContract(SmartContractError),
+    #[error("dao error: {0}")]
+    Dao(DaoError),
+    // module error from a pallet that has no typed error mapping
+    #[error("module error: {0}")]
+    Module(ModuleError),
+    // a dispatch error that is not coming from a pallet
+    #[error("dispatch error: {0}")]
+    Dispatch(DispatchError),
+    // the extrinsic was executed but the event it should emit was not found
+    #[error("expected event {pallet}::{event} was not emitted")]
+    EventNotFound {
+        pallet: &'static str,
+        event: &'static str,
+    },
+    // none of the bundled runtimes match the metadata of the node
+    #[error("node runtime (spec version {spec_version}, genesis {genesis_hash:?}) does not match any bundled runtime")]
+    UnsupportedRuntime {
+        spec_version: u32,
+        genesis_hash: Hash,
+    },
+    // the runtime of the node does not have the calls or storage the operation needs
+    #[error("{feature} is not supported on the {runtime} runtime")]
+    FeatureUnsupportedOnRuntime {
+        runtime: &'static str,
+        feature: &'static str,
+    },
+    // the runtime of the node requires a field the input left empty
+    #[error("{field} is required on the {runtime} runtime")]
+    MissingField {
+        runtime: &'static str,
+        field: &'static str,
+    },
+    // the node was upgraded to a runtime that changed pallets used by the client
+    #[error("node runtime was upgraded to spec version {spec_version} which changed pallets {pallets:?}")]
+    IncompatibleRuntime {
+        spec_version: u32,
+        pallets: Vec<String>,
+    },
+    // the runtime upgrades subscription was lost, so the runtime may have changed
+    #[error("runtime upgrades are not watched anymore: {0}")]
+    UpgradesNotWatched(String),
+    // the signer failed or refused to sign an extrinsic
+    #[error("signer error: {0}")]
+    Signer(String),
+    // a key could not be created or derived
+    #[error("invalid key: {0:?}")]
+    InvalidKey(SecretStringError),
+    // the extrinsic did not get as far as requested in time
+    #[error("timed out after {0:?} waiting for the extrinsic")]
+    Timeout(Duration),
+    // the node does not know the block
+    #[error("block {0:?} was not found")]
+    BlockNotFound(Hash),
+    // a storage proof does not prove the value against the header
+    #[error("invalid storage proof: {0}")]
+    InvalidProof(String),
+    // the operation needs a subscription, which the http transport does not support
+    #[error("{0} needs a subscription, which is not supported over http")]
+    SubscriptionUnsupported(String),
+    #[error(transparent)]
+    Subxt(subxt::Error),
+}
+
+impl Error {
+    pub fn event_not_found<E: StaticEvent>() -> Self {
+        Error::EventNotFound {
+            pallet: E::PALLET,
+            event: E::EVENT,
+        }
+    }
+}
+
+impl From<subxt::Error> for Error {
+    fn from(err: subxt::Error) -> Self {
+        match err {
+            subxt::Error::Rpc(err) => err.into(),
+            subxt::Error::Codec(_) | subxt::Error::DecodeValue(_) => Error::Decode(err),
+            subxt::Error::Runtime(err) => err.into(),
+            err => Error::Subxt(err),
+        }
+    }
+}
+
+impl From<RpcError> for Error {
+    fn from(err: RpcError) -> Self {
+        match err {
+            RpcError::ClientError(err) => match err.downcast::<SubscriptionUnsupported>() {
+                Ok(err) => Error::SubscriptionUnsupported(err.0),
+                Err(err) => Error::Rpc(RpcError::ClientError(err)),
+            },
+            err => Error::Rpc(err),
+        }
+    }
+}
+
+impl From<SecretStringError> for Error {
+    fn from(err: SecretStringError) -> Self {
+        Error::InvalidKey(err)
+    }
+}
+
+impl From<DispatchError> for Error {
+    fn from(err: DispatchError) -> Self {
+        match err {
+            DispatchError::Module(err) => err.into(),
+            err => Error::Dispatch(err),
+        }
+    }
+}
+
+impl From<ModuleError> for Error {
+    fn from(err: ModuleError) -> Self {
+        let typed = match err.pallet.as_str() {
+            TfgridError::PALLET => TfgridError::from_name(&err.error).map(Error::Tfgrid),
+            SmartContractError::PALLET => {
+                SmartContractError::from_name(&err.error).map(Error::SmartContract)
+            }
+            DaoError::PALLET => DaoError::from_name(&err.error).map(Error::Dao),
+            _ => None,
+        };
+
+        typed.unwrap_or(Error::Module(err))
+    }
+}
+
+macro_rules! pallet_error {
+    ($name:ident, $pallet:literal, [$($variant:ident),+ $(,)?]) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant),+
+        }
+
+        impl $name {
+            pub const PALLET: &'static str = $pallet;
+
+            // maps the error name as found in the runtime metadata to its variant
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($variant) => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant),)+
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}::{}", Self::PALLET, self.name())
+            }
+        }
+    };
+}
+
+pallet_error!(
+    TfgridError,
+    "TfgridModule",
+    [
+        NoneValue,
+        StorageOverflow,
+        CannotCreateNode,
+        NodeNotExists,
+        NodeWithTwinIdExists,
+        CannotDeleteNode,
+        NodeDeleteNotAuthorized,
+        NodeUpdateNotAuthorized,
+        FarmExists,
+        FarmNotExists,
+        CannotCreateFarmWrongTwin,
+        CannotUpdateFarmWrongTwin,
+        CannotDeleteFarm,
+        CannotDeleteFarmWithPublicIPs,
+        CannotDeleteFarmWithNodesAssigned,
+        CannotDeleteFarmWrongTwin,
+        IpExists,
+        IpNotExists,
+        EntityWithNameExists,
+        EntityWithPubkeyExists,
+        EntityNotExists,
+        EntitySignatureDoesNotMatch,
+        EntityWithSignatureAlreadyExists,
+        CannotUpdateEntity,
+        CannotDeleteEntity,
+        SignatureLengthIsIncorrect,
+        TwinExists,
+        TwinNotExists,
+        TwinWithPubkeyExists,
+        CannotCreateTwin,
+        UnauthorizedToUpdateTwin,
+        PricingPolicyExists,
+        PricingPolicyNotExists,
+        PricingPolicyWithDifferentIdExists,
+        CertificationCodeExists,
+        FarmingPolicyAlreadyExists,
+        FarmPayoutAdressAlreadyRegistered,
+        FarmerDoesNotHaveEnoughFunds,
+        UserDidNotSignTermsAndConditions,
+        FarmerDidNotSignTermsAndConditions,
+        FarmerNotAuthorized,
+        InvalidFarmName,
+        AlreadyCertifier,
+        NotCertifier,
+        NotAllowedToCertifyNode,
+        FarmingPolicyNotExists,
+        RelayTooShort,
+        RelayTooLong,
+        InvalidRelay,
+        FarmNameTooShort,
+        FarmNameTooLong,
+        InvalidPublicIP,
+        PublicIPTooShort,
+        PublicIPTooLong,
+        GatewayIPTooShort,
+        GatewayIPTooLong,
+        IP4TooShort,
+        IP4TooLong,
+        InvalidIP4,
+        GW4TooShort,
+        GW4TooLong,
+        InvalidGW4,
+        IP6TooShort,
+        IP6TooLong,
+        InvalidIP6,
+        GW6TooShort,
+        GW6TooLong,
+        InvalidGW6,
+        DomainTooShort,
+        DomainTooLong,
+        InvalidDomain,
+        MethodIsDeprecated,
+        InterfaceNameTooShort,
+        InterfaceNameTooLong,
+        InvalidInterfaceName,
+        InterfaceMacTooShort,
+        InterfaceMacTooLong,
+        InvalidMacAddress,
+        InterfaceIpTooShort,
+        InterfaceIpTooLong,
+        InvalidInterfaceIP,
+        InvalidZosVersion,
+        FarmingPolicyExpired,
+        InvalidHRUInput,
+        InvalidSRUInput,
+        InvalidCRUInput,
+        InvalidMRUInput,
+        LatitudeInputTooShort,
+        LatitudeInputTooLong,
+        InvalidLatitudeInput,
+        LongitudeInputTooShort,
+        LongitudeInputTooLong,
+        InvalidLongitudeInput,
+        CountryNameTooShort,
+        CountryNameTooLong,
+        InvalidCountryName,
+        CityNameTooShort,
+        CityNameTooLong,
+        InvalidCityName,
+        InvalidCountryCityPair,
+        SerialNumberTooShort,
+        SerialNumberTooLong,
+        InvalidSerialNumber,
+        DocumentLinkInputTooShort,
+        DocumentLinkInputTooLong,
+        InvalidDocumentLinkInput,
+        DocumentHashInputTooShort,
+        DocumentHashInputTooLong,
+        InvalidDocumentHashInput,
+        InvalidPublicConfig,
+        UnauthorizedToChangePowerTarget,
+        InvalidRelayAddress,
+        TwinIpTooShort,
+        TwinIpTooLong,
+        InvalidTwinIp,
+    ]
+);
+
+pallet_error!(
+    SmartContractError,
+    "SmartContractModule",
+    [
+        TwinNotExists,
+        NodeNotExists,
+        FarmNotExists,
+        FarmHasNotEnoughPublicIPs,
+        FarmHasNotEnoughPublicIPsFree,
+        FailedToReserveIP,
+        FailedToFreeIPs,
+        ContractNotExists,
+        TwinNotAuthorizedToUpdateContract,
+        TwinNotAuthorizedToCancelContract,
+        NodeNotAuthorizedToDeployContract,
+        NodeNotAuthorizedToComputeReport,
+        PricingPolicyNotExists,
+        ContractIsNotUnique,
+        NameExists,
+        NameNotValid,
+        InvalidContractType,
+        TFTPriceValueError,
+        NotEnoughResourcesOnNode,
+        NodeNotAuthorizedToReportResources,
+        MethodIsDeprecated,
+        NodeHasActiveContracts,
+        NodeHasRentContract,
+        NodeIsNotDedicated,
+        NodeNotAvailableToDeploy,
+        CannotUpdateContractInGraceState,
+        NumOverflow,
+        OffchainSignedTxCannotSign,
+        OffchainSignedTxAlreadySent,
+        OffchainSignedTxNoLocalAccountAvailable,
+        NameContractNameTooShort,
+        NameContractNameTooLong,
+        InvalidProviderConfiguration,
+        NoSuchSolutionProvider,
+        SolutionProviderNotApproved,
+        TwinNotAuthorized,
+        ServiceContractNotExists,
+        ServiceContractCreationNotAllowed,
+        ServiceContractModificationNotAllowed,
+        ServiceContractApprovalNotAllowed,
+        ServiceContractRejectionNotAllowed,
+        ServiceContractBillingNotApprovedByBoth,
+        ServiceContractBillingVariableAmountTooHigh,
+        ServiceContractBillMetadataTooLong,
+        ServiceContractMetadataTooLong,
+        ServiceContractNotEnoughFundsToPayBill,
+        CanOnlyIncreaseFrequency,
+        IsNotAnAuthority,
+        WrongAuthority,
+    ]
+);
+
+pallet_error!(
+    DaoError,
+    "Dao",
+    [
+        NoneValue,
+        StorageOverflow,
+        FarmNotExists,
+        NotCouncilMember,
+        WrongProposalLength,
+        DuplicateProposal,
+        NotAuthorizedToVote,
+        ProposalMissing,
+        WrongIndex,
+        DuplicateVote,
+        WrongProposalWeight,
+        TooEarly,
+        TimeLimitReached,
+        OngoingVoteAndTresholdStillNotMet,
+        FarmHasNoNodes,
+        InvalidProposalDuration,
+    ]
+);
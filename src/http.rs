@@ -0,0 +1,203 @@
+use crate::error::Error;
+use crate::rpc::{client_error, Params};
+use jsonrpsee::{
+    core::client::ClientT,
+    http_client::{HttpClient, HttpClientBuilder},
+};
+use subxt::{
+    error::RpcError,
+    rpc::{RawValue, RpcClientT, RpcFuture, RpcSubscription},
+};
+
+// HttpRpc sends every request as an HTTP POST, so no connection is held to the
+// node between requests. Subscriptions are not supported over HTTP, opening one
+// fails with `SubscriptionUnsupported`.
+#[derive(Debug, Clone)]
+pub(crate) struct HttpRpc {
+    client: HttpClient,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("subscription {0} is not supported over http")]
+pub(crate) struct SubscriptionUnsupported(pub(crate) String);
+
+impl HttpRpc {
+    pub(crate) fn new(url: &str) -> Result<Self, Error> {
+        let client = HttpClientBuilder::default()
+            .build(url)
+            .map_err(client_error)?;
+
+        Ok(HttpRpc { client })
+    }
+}
+
+// urls with an http(s) scheme are served by `HttpRpc`, others by a websocket
+pub(crate) fn is_http(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+impl RpcClientT for HttpRpc {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<RawValue>>,
+    ) -> RpcFuture<'a, Box<RawValue>> {
+        Box::pin(async move {
+            ClientT::request(&self.client, method, Params(params))
+                .await
+                .map_err(client_error)
+        })
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        _params: Option<Box<RawValue>>,
+        _unsub: &'a str,
+    ) -> RpcFuture<'a, RpcSubscription> {
+        Box::pin(async move {
+            Err(RpcError::ClientError(Box::new(SubscriptionUnsupported(
+                sub.to_string(),
+            ))))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Client, Runtime};
+    use crate::nonce::is_nonce_error;
+    use crate::runtimes::testing;
+    use jsonrpsee::{
+        server::{RpcModule, ServerBuilder, ServerHandle},
+        types::error::{CallError, ErrorObject},
+    };
+    use std::net::SocketAddr;
+
+    // a devnet node, its pool rejects every extrinsic as outdated like substrate does
+    async fn node() -> (SocketAddr, ServerHandle) {
+        let mut module = RpcModule::new(());
+        for method in [
+            "chain_getBlockHash",
+            "state_getRuntimeVersion",
+            "state_getMetadata",
+        ] {
+            module
+                .register_method(method, move |_, _| {
+                    Ok(testing::client_response("devnet", method).expect("client request"))
+                })
+                .unwrap();
+        }
+        module
+            .register_method::<(), _>("author_submitExtrinsic", |_, _| {
+                Err(CallError::Custom(ErrorObject::owned(
+                    1010,
+                    "Invalid Transaction",
+                    Some("Transaction is outdated"),
+                ))
+                .into())
+            })
+            .unwrap();
+
+        let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        (addr, server.start(module).unwrap())
+    }
+
+    #[test]
+    fn http_urls_are_detected() {
+        for url in [
+            "http://127.0.0.1:9933",
+            "https://tfchain.grid.tf",
+            "HTTPS://node",
+        ] {
+            assert!(is_http(url), "{}", url);
+        }
+        for url in [
+            "ws://127.0.0.1:9944",
+            "wss://tfchain.grid.tf",
+            "127.0.0.1",
+            "httpx://node",
+        ] {
+            assert!(!is_http(url), "{}", url);
+        }
+
+        assert!(HttpRpc::new("http://127.0.0.1:9933").is_ok());
+        assert!(HttpRpc::new("ws://127.0.0.1:9944").is_err());
+    }
+
+    #[tokio::test]
+    async fn the_scheme_selects_the_transport() {
+        let (addr, _handle) = node().await;
+
+        // upgrades are only watched over a websocket
+        let client = Client::new(format!("http://{}", addr), Runtime::Devnet)
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.runtime_upgrades(),
+            Err(Error::SubscriptionUnsupported(_))
+        ));
+
+        let client = Client::new(format!("ws://{}", addr), Runtime::Devnet)
+            .await
+            .unwrap();
+        assert!(client.runtime_upgrades().is_ok());
+    }
+
+    #[tokio::test]
+    async fn request_errors_are_mapped() {
+        let (addr, _handle) = node().await;
+        let rpc = HttpRpc::new(&format!("http://{}", addr)).unwrap();
+
+        // the error returned by the node is kept for the callers to inspect
+        let err: Error = rpc
+            .request_raw("author_submitExtrinsic", None)
+            .await
+            .unwrap_err()
+            .into();
+        assert!(
+            matches!(err, Error::Rpc(RpcError::ClientError(_))),
+            "{}",
+            err
+        );
+        assert!(is_nonce_error(&err), "{}", err);
+
+        let err: Error = rpc
+            .request_raw("unknown_method", None)
+            .await
+            .unwrap_err()
+            .into();
+        assert!(!is_nonce_error(&err), "{}", err);
+
+        let unreachable = HttpRpc::new("http://127.0.0.1:1").unwrap();
+        let err: Error = unreachable
+            .request_raw("chain_getBlockHash", None)
+            .await
+            .unwrap_err()
+            .into();
+        assert!(
+            matches!(err, Error::Rpc(RpcError::ClientError(_))),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn subscriptions_are_unsupported() {
+        let rpc = HttpRpc::new("http://127.0.0.1:1").unwrap();
+
+        let err: Error = rpc
+            .subscribe_raw("chain_subscribeNewHeads", None, "chain_unsubscribeNewHeads")
+            .await
+            .err()
+            .unwrap()
+            .into();
+        match err {
+            Error::SubscriptionUnsupported(sub) => assert_eq!(sub, "chain_subscribeNewHeads"),
+            err => panic!("unexpected error {}", err),
+        }
+    }
+}
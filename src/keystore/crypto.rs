@@ -0,0 +1,124 @@
+// scrypt (RFC 7914) and the NaCl secretbox (xsalsa20-poly1305) used by the
+// polkadot-js keyring to encrypt exported accounts.
+use crypto_secretbox::aead::{Aead, KeyInit};
+use crypto_secretbox::{Key, Nonce, XSalsa20Poly1305};
+
+pub const KEY_LENGTH: usize = 32;
+pub const NONCE_LENGTH: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScryptParams {
+    pub n: u32,
+    pub p: u32,
+    pub r: u32,
+}
+
+// the parameters polkadot-js uses for new exports
+impl Default for ScryptParams {
+    fn default() -> Self {
+        ScryptParams {
+            n: 1 << 15,
+            p: 1,
+            r: 8,
+        }
+    }
+}
+
+impl ScryptParams {
+    // only the parameters polkadot-js writes are accepted, the ones read from an
+    // imported file could otherwise make the key derivation allocate gigabytes
+    pub fn is_valid(&self) -> bool {
+        *self == ScryptParams::default()
+    }
+}
+
+pub fn scrypt(password: &[u8], salt: &[u8], params: ScryptParams, out: &mut [u8]) {
+    let params = scrypt::Params::new(params.n.trailing_zeros() as u8, params.r, params.p)
+        .expect("scrypt parameters are valid");
+
+    scrypt::scrypt(password, salt, &params, out).expect("scrypt output length is valid");
+}
+
+pub fn secretbox_seal(key: &[u8; KEY_LENGTH], nonce: &[u8; NONCE_LENGTH], msg: &[u8]) -> Vec<u8> {
+    XSalsa20Poly1305::new(&Key::from(*key))
+        .encrypt(&Nonce::from(*nonce), msg)
+        .expect("the message fits in a secretbox")
+}
+
+// decrypts a box created by `secretbox_seal`, returns `None` if authentication fails
+pub fn secretbox_open(
+    key: &[u8; KEY_LENGTH],
+    nonce: &[u8; NONCE_LENGTH],
+    sealed: &[u8],
+) -> Option<Vec<u8>> {
+    XSalsa20Poly1305::new(&Key::from(*key))
+        .decrypt(&Nonce::from(*nonce), sealed)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // test vectors from RFC 7914 section 12
+    #[test]
+    fn scrypt_vectors() {
+        let vectors: [(&str, &str, u32, u32, u32, &str); 3] = [
+            ("", "", 16, 1, 1, "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"),
+            ("password", "NaCl", 1024, 8, 16, "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"),
+            ("pleaseletmein", "SodiumChloride", 16384, 8, 1, "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887"),
+        ];
+
+        for (password, salt, n, r, p, expected) in vectors {
+            let mut out = [0u8; 64];
+            scrypt(
+                password.as_bytes(),
+                salt.as_bytes(),
+                ScryptParams { n, p, r },
+                &mut out,
+            );
+            assert_eq!(hex::encode(out), expected);
+        }
+    }
+
+    // test vector from the NaCl secretbox tests (tests/secretbox.c)
+    #[test]
+    fn secretbox_vector() {
+        let key: [u8; KEY_LENGTH] =
+            hex::decode("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let nonce: [u8; NONCE_LENGTH] =
+            hex::decode("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let msg = hex::decode("be075fc53c81f2d5cf141316ebeb0c7b5228c52a4c62cbd44b66849b64244ffce5ecbaaf33bd751a1ac728d45e6c61296cdc3c01233561f41db66cce314adb310e3be8250c46f06dceea3a7fa1348057e2f6556ad6b1318a024a838f21af1fde048977eb48f59ffd4924ca1c60902e52f0a089bc76897040e082f937763848645e0705").unwrap();
+        let sealed = hex::decode("f3ffc7703f9400e52a7dfb4b3d3305d98e993b9f48681273c29650ba32fc76ce48332ea7164d96a4476fb8c531a1186ac0dfc17c98dce87b4da7f011ec48c97271d2c20f9b928fe2270d6fb863d51738b48eeee314a7cc8ab932164548e526ae90224368517acfeabd6bb3732bc0e9da99832b61ca01b6de56244a9e88d5f9b37973f622a43d14a6599b1f654cb45a74e355a5").unwrap();
+
+        assert_eq!(secretbox_seal(&key, &nonce, &msg), sealed);
+        assert_eq!(secretbox_open(&key, &nonce, &sealed), Some(msg));
+
+        let mut tampered = sealed;
+        tampered[20] ^= 1;
+        assert_eq!(secretbox_open(&key, &nonce, &tampered), None);
+    }
+
+    #[test]
+    fn only_polkadot_js_params_are_valid() {
+        assert!(ScryptParams::default().is_valid());
+        assert!(!ScryptParams {
+            n: 1 << 20,
+            p: 1,
+            r: 16
+        }
+        .is_valid());
+        assert!(!ScryptParams {
+            n: 1 << 14,
+            p: 1,
+            r: 8
+        }
+        .is_valid());
+    }
+}
@@ -0,0 +1,478 @@
+use crate::client::{KeyPair, KeyType};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use subxt::ext::sp_core::{ed25519, sr25519, Pair};
+
+mod crypto;
+
+use crypto::{ScryptParams, KEY_LENGTH, NONCE_LENGTH};
+
+// prefix used for the addresses of exported accounts, the generic substrate one
+const SS58_PREFIX: u16 = 42;
+
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+const SALT_LENGTH: usize = 32;
+const SCRYPT_LENGTH: usize = SALT_LENGTH + 3 * 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeystoreError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid keyring json: {0}")]
+    Json(#[from] serde_json::Error),
+    // the data does not follow the polkadot-js export format
+    #[error("invalid keyring data: {0}")]
+    InvalidFormat(&'static str),
+    #[error("unsupported encoding {0:?}")]
+    UnsupportedEncoding(Vec<String>),
+    #[error("invalid passphrase")]
+    InvalidPassphrase,
+    #[error("invalid alias '{0}'")]
+    InvalidAlias(String),
+    #[error("alias '{0}' already exists")]
+    AliasExists(String),
+    #[error("alias '{0}' not found")]
+    NotFound(String),
+}
+
+// KeyringJson is an encrypted account in the polkadot-js keyring export format, it
+// can be imported in the polkadot.js UI and vice versa.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyringJson {
+    pub encoded: String,
+    pub encoding: Encoding,
+    pub address: String,
+    #[serde(default)]
+    pub meta: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Encoding {
+    pub content: Vec<String>,
+    #[serde(rename = "type")]
+    pub kind: Vec<String>,
+    pub version: String,
+}
+
+impl KeyringJson {
+    // encrypts the key pair with the passphrase, `name` is stored in the meta data
+    // and shown as the account name by polkadot.js
+    pub fn encrypt(kp: &KeyPair, passphrase: &str, name: Option<&str>) -> Self {
+        let (secret, key_type) = match kp {
+            KeyPair::Sr25519(pair) => {
+                let keypair: &schnorrkel::Keypair = pair.as_ref();
+                (keypair.secret.to_ed25519_bytes().to_vec(), "sr25519")
+            }
+            KeyPair::Ed25519(pair) => {
+                let mut secret = pair.seed().to_vec();
+                secret.extend_from_slice(&kp.public());
+                (secret, "ed25519")
+            }
+        };
+
+        let mut plain = PKCS8_HEADER.to_vec();
+        plain.extend_from_slice(&secret);
+        plain.extend_from_slice(&PKCS8_DIVIDER);
+        plain.extend_from_slice(&kp.public());
+
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; SALT_LENGTH];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rng.fill_bytes(&mut nonce);
+
+        let params = ScryptParams::default();
+        let key = password_key(passphrase, &salt, params);
+
+        let mut encoded = salt.to_vec();
+        encoded.extend_from_slice(&params.n.to_le_bytes());
+        encoded.extend_from_slice(&params.p.to_le_bytes());
+        encoded.extend_from_slice(&params.r.to_le_bytes());
+        encoded.extend_from_slice(&nonce);
+        encoded.extend_from_slice(&crypto::secretbox_seal(&key, &nonce, &plain));
+
+        let mut meta = serde_json::Map::new();
+        if let Some(name) = name {
+            meta.insert("name".into(), name.into());
+        }
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        meta.insert("whenCreated".into(), created.into());
+
+        KeyringJson {
+            encoded: base64::encode(encoded),
+            encoding: Encoding {
+                content: vec!["pkcs8".into(), key_type.into()],
+                kind: vec!["scrypt".into(), "xsalsa20-poly1305".into()],
+                version: "3".into(),
+            },
+            address: kp.to_ss58(SS58_PREFIX),
+            meta,
+        }
+    }
+
+    // the type of the encrypted key pair
+    pub fn key_type(&self) -> Result<KeyType, KeystoreError> {
+        match self.encoding.content.as_slice() {
+            [pkcs8, key_type] if pkcs8 == "pkcs8" => KeyType::from_str(key_type)
+                .map_err(|_| KeystoreError::UnsupportedEncoding(self.encoding.content.clone())),
+            _ => Err(KeystoreError::UnsupportedEncoding(
+                self.encoding.content.clone(),
+            )),
+        }
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<KeyPair, KeystoreError> {
+        let key_type = self.key_type()?;
+        if self.encoding.version != "3" || self.encoding.kind != ["scrypt", "xsalsa20-poly1305"] {
+            return Err(KeystoreError::UnsupportedEncoding(
+                self.encoding.kind.clone(),
+            ));
+        }
+
+        let encoded = base64::decode(&self.encoded)
+            .map_err(|_| KeystoreError::InvalidFormat("encoded data is not base64"))?;
+        if encoded.len() < SCRYPT_LENGTH + NONCE_LENGTH {
+            return Err(KeystoreError::InvalidFormat("encoded data is too short"));
+        }
+
+        let word = |i: usize| {
+            u32::from_le_bytes([encoded[i], encoded[i + 1], encoded[i + 2], encoded[i + 3]])
+        };
+        let params = ScryptParams {
+            n: word(SALT_LENGTH),
+            p: word(SALT_LENGTH + 4),
+            r: word(SALT_LENGTH + 8),
+        };
+        if !params.is_valid() {
+            return Err(KeystoreError::InvalidFormat("invalid scrypt parameters"));
+        }
+
+        let key = password_key(passphrase, &encoded[..SALT_LENGTH], params);
+        let mut nonce = [0u8; NONCE_LENGTH];
+        nonce.copy_from_slice(&encoded[SCRYPT_LENGTH..SCRYPT_LENGTH + NONCE_LENGTH]);
+
+        let plain = crypto::secretbox_open(&key, &nonce, &encoded[SCRYPT_LENGTH + NONCE_LENGTH..])
+            .ok_or(KeystoreError::InvalidPassphrase)?;
+
+        decode_pkcs8(key_type, &plain)
+    }
+}
+
+fn password_key(passphrase: &str, salt: &[u8], params: ScryptParams) -> [u8; KEY_LENGTH] {
+    let mut out = [0u8; 64];
+    crypto::scrypt(passphrase.as_bytes(), salt, params, &mut out);
+
+    let mut key = [0u8; KEY_LENGTH];
+    key.copy_from_slice(&out[..KEY_LENGTH]);
+    key
+}
+
+fn decode_pkcs8(key_type: KeyType, plain: &[u8]) -> Result<KeyPair, KeystoreError> {
+    if !plain.starts_with(&PKCS8_HEADER) {
+        return Err(KeystoreError::InvalidFormat("invalid pkcs8 header"));
+    }
+    let rest = &plain[PKCS8_HEADER.len()..];
+
+    // the secret is either a 64 bytes secret key or a 32 bytes seed
+    let (secret, public) = [64, 32]
+        .iter()
+        .find_map(|&len| {
+            let divider = rest.get(len..len + PKCS8_DIVIDER.len())?;
+            let public = rest.get(len + PKCS8_DIVIDER.len()..len + PKCS8_DIVIDER.len() + 32)?;
+            (divider == PKCS8_DIVIDER).then(|| (&rest[..len], public))
+        })
+        .ok_or(KeystoreError::InvalidFormat("invalid pkcs8 divider"))?;
+
+    let kp: KeyPair = match (key_type, secret.len()) {
+        (KeyType::Sr25519, 64) => {
+            let secret = schnorrkel::SecretKey::from_ed25519_bytes(secret)
+                .map_err(|_| KeystoreError::InvalidFormat("invalid sr25519 secret key"))?;
+            sr25519::Pair::from(secret.to_keypair()).into()
+        }
+        (KeyType::Sr25519, _) => sr25519::Pair::from_seed_slice(secret)
+            .map_err(|_| KeystoreError::InvalidFormat("invalid sr25519 seed"))?
+            .into(),
+        (KeyType::Ed25519, _) => ed25519::Pair::from_seed_slice(&secret[..32])
+            .map_err(|_| KeystoreError::InvalidFormat("invalid ed25519 seed"))?
+            .into(),
+    };
+
+    if kp.public() != public {
+        return Err(KeystoreError::InvalidFormat(
+            "public key does not match the secret key",
+        ));
+    }
+
+    Ok(kp)
+}
+
+// KeystoreEntry describes an account stored in the keystore
+#[derive(Debug, Clone)]
+pub struct KeystoreEntry {
+    pub alias: String,
+    pub address: String,
+    pub key_type: KeyType,
+}
+
+// Keystore stores encrypted key pairs in a directory, one polkadot-js JSON file
+// per account named after its alias.
+#[derive(Debug, Clone)]
+pub struct Keystore {
+    path: PathBuf,
+}
+
+impl Keystore {
+    // opens the keystore at the given directory, creating it if needed. A directory
+    // created by the keystore is only accessible by its owner.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, KeystoreError> {
+        let path = path.as_ref().to_path_buf();
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        builder.mode(0o700);
+        builder.create(&path)?;
+
+        Ok(Keystore { path })
+    }
+
+    // encrypts and stores the key pair under the alias
+    pub fn add(&self, alias: &str, kp: &KeyPair, passphrase: &str) -> Result<(), KeystoreError> {
+        let json = KeyringJson::encrypt(kp, passphrase, Some(alias));
+        self.store(alias, &json)
+    }
+
+    // decrypts the key pair stored under the alias
+    pub fn get(&self, alias: &str, passphrase: &str) -> Result<KeyPair, KeystoreError> {
+        self.load(alias)?.decrypt(passphrase)
+    }
+
+    // imports an account exported from polkadot.js. The passphrase is checked before
+    // the account is stored.
+    pub fn import(
+        &self,
+        alias: &str,
+        json: &str,
+        passphrase: &str,
+    ) -> Result<KeyPair, KeystoreError> {
+        let json: KeyringJson = serde_json::from_str(json)?;
+        let kp = json.decrypt(passphrase)?;
+        self.store(alias, &json)?;
+
+        Ok(kp)
+    }
+
+    // returns the stored account in the polkadot-js export format
+    pub fn export(&self, alias: &str) -> Result<String, KeystoreError> {
+        Ok(serde_json::to_string(&self.load(alias)?)?)
+    }
+
+    // lists the accounts in the keystore, files that are not accounts in a supported
+    // format are skipped
+    pub fn list(&self) -> Result<Vec<KeystoreEntry>, KeystoreError> {
+        let mut entries = vec![];
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension() != Some("json".as_ref()) {
+                continue;
+            }
+
+            let alias = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(alias) => alias.to_string(),
+                None => continue,
+            };
+            let json = match self.load(&alias) {
+                Ok(json) => json,
+                Err(_) => continue,
+            };
+            let key_type = match json.key_type() {
+                Ok(key_type) => key_type,
+                Err(_) => continue,
+            };
+            entries.push(KeystoreEntry {
+                key_type,
+                address: json.address,
+                alias,
+            });
+        }
+        entries.sort_by(|a, b| a.alias.cmp(&b.alias));
+
+        Ok(entries)
+    }
+
+    pub fn delete(&self, alias: &str) -> Result<(), KeystoreError> {
+        match fs::remove_file(self.file(alias)?) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Err(KeystoreError::NotFound(alias.into()))
+            }
+            res => Ok(res?),
+        }
+    }
+
+    fn file(&self, alias: &str) -> Result<PathBuf, KeystoreError> {
+        let valid = !alias.is_empty()
+            && !alias.starts_with('.')
+            && alias
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+        if !valid {
+            return Err(KeystoreError::InvalidAlias(alias.into()));
+        }
+
+        Ok(self.path.join(format!("{}.json", alias)))
+    }
+
+    fn load(&self, alias: &str) -> Result<KeyringJson, KeystoreError> {
+        match fs::read(self.file(alias)?) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Err(KeystoreError::NotFound(alias.into()))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // the file is created readable by its owner only
+    fn store(&self, alias: &str, json: &KeyringJson) -> Result<(), KeystoreError> {
+        let path = self.file(alias)?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let file = options.open(&path).map_err(|err| match err.kind() {
+            io::ErrorKind::AlreadyExists => KeystoreError::AliasExists(alias.into()),
+            _ => err.into(),
+        })?;
+
+        Ok(serde_json::to_writer_pretty(file, json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+
+    // accounts of the substrate dev phrase encrypted with the passphrase "passphrase"
+    const SR25519_JSON: &str = r#"{"encoded":"8vK1MDMZOWuTqT0p1LHEIhDEsNhc+Myf9+HtIxOo7tQAgAAAAQAAAAgAAAB689Xm8M7bWblBb60L867lZ8NXH5D8LCs/KK4YxeeX0MNp0l6tdxIg2XBxll/uwHZ4sTnX+zWDtR6squOoOz7KtYCkeqQp4WiYOBwWvqSzMOcVKJ6CaB6XUx51udJMXAMoevnt0hwDbMaT9XUffNyAcDG2AHGSAL0SqpoMxtWsus0Z7Vybt1P+7EwWjVyw9Fr24+1YqtK3Q4XOO5N8","encoding":{"content":["pkcs8","sr25519"],"type":["scrypt","xsalsa20-poly1305"],"version":"3"},"address":"5DfhGyQdFobKM8NsWvEeAKk5EQQgYe9AydgJ7rMB6E1EqRzV","meta":{"name":"fixture","whenCreated":1700000000000}}"#;
+    const ED25519_JSON: &str = r#"{"encoded":"tH02KRCNZUYqTEagWbmuWBwkqq/R+2rlwguJQu7P/kcAgAAAAQAAAAgAAACRnnTQYCxWPyCVchdmZIO3Lo0bV1Fuzs99B6kSQ6r9kATHCvYDkKweaYJ9jRndW2DRPV2u7OY1pAWzC5u9CEMLNWWInA9h3DJ4TnjxfFueXmFcXwLmybm3OkfiYV0PaXvYcuGDMczAL9Xn7ECfIY4fIc42P1VDTvpMJTm/9ggEF4B3u5GJ11KHthDB8ihpqqwecpq2QEuaItxBTAvw","encoding":{"content":["pkcs8","ed25519"],"type":["scrypt","xsalsa20-poly1305"],"version":"3"},"address":"5DFJF7tY4bpbpcKPJcBTQaKuCDEPCpiz8TRjpmLeTtweqmXL","meta":{"name":"fixture","whenCreated":1700000000000}}"#;
+
+    // a directory removed when the test ends
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let name = format!("tfchain-keystore-{}", rand::random::<u64>());
+            TempDir(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn decrypts_keyring_json() {
+        for (json, key_type, public) in [
+            (
+                SR25519_JSON,
+                KeyType::Sr25519,
+                "46ebddef8cd9bb167dc30878d7113b7e168e6f0646beffd77d69d39bad76b47a",
+            ),
+            (
+                ED25519_JSON,
+                KeyType::Ed25519,
+                "345071da55e5dccefaaa440339415ef9f2663338a38f7da0df21be5ab4e055ef",
+            ),
+        ] {
+            let json: KeyringJson = serde_json::from_str(json).unwrap();
+            let kp = json.decrypt("passphrase").unwrap();
+
+            assert_eq!(hex::encode(kp.public()), public);
+            assert_eq!(
+                kp.public(),
+                KeyPair::from_phrase(key_type, PHRASE, None)
+                    .unwrap()
+                    .public()
+            );
+            assert!(matches!(
+                json.decrypt("wrong"),
+                Err(KeystoreError::InvalidPassphrase)
+            ));
+        }
+    }
+
+    #[test]
+    fn encrypt_round_trip() {
+        for key_type in [KeyType::Sr25519, KeyType::Ed25519] {
+            let kp = KeyPair::from_phrase(key_type, PHRASE, None).unwrap();
+            let json = KeyringJson::encrypt(&kp, "secret", Some("alice"));
+            let json: KeyringJson =
+                serde_json::from_str(&serde_json::to_string(&json).unwrap()).unwrap();
+
+            assert_eq!(json.address, kp.to_ss58(SS58_PREFIX));
+            assert_eq!(json.meta["name"], "alice");
+            assert_eq!(json.decrypt("secret").unwrap().public(), kp.public());
+        }
+    }
+
+    #[test]
+    fn rejects_expensive_scrypt_params() {
+        let mut json: KeyringJson = serde_json::from_str(SR25519_JSON).unwrap();
+        let mut encoded = base64::decode(&json.encoded).unwrap();
+        encoded[SALT_LENGTH..SALT_LENGTH + 4].copy_from_slice(&(1u32 << 20).to_le_bytes());
+        encoded[SALT_LENGTH + 8..SALT_LENGTH + 12].copy_from_slice(&16u32.to_le_bytes());
+        json.encoded = base64::encode(encoded);
+
+        assert!(matches!(
+            json.decrypt("passphrase"),
+            Err(KeystoreError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn list_skips_foreign_files() {
+        let dir = TempDir::new();
+        let keystore = Keystore::open(&dir.0).unwrap();
+        keystore
+            .import("alice", SR25519_JSON, "passphrase")
+            .unwrap();
+        fs::write(dir.0.join("broken.json"), "{").unwrap();
+        fs::write(dir.0.join("other.json"), r#"{"name": "not a key"}"#).unwrap();
+
+        let entries = keystore.list().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].alias, "alice");
+        assert!(matches!(entries[0].key_type, KeyType::Sr25519));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new();
+        let keystore = Keystore::open(dir.0.join("keys")).unwrap();
+        keystore
+            .import("alice", ED25519_JSON, "passphrase")
+            .unwrap();
+
+        let mode = |path: PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(dir.0.join("keys")), 0o700);
+        assert_eq!(mode(dir.0.join("keys").join("alice.json")), 0o600);
+    }
+}
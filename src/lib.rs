@@ -1,2 +1,18 @@
 pub mod client;
+pub mod error;
+pub mod http;
+pub mod keystore;
+#[cfg(feature = "light-client")]
+pub mod light;
+pub mod nonce;
+pub mod proof;
+pub mod retry;
+pub mod rpc;
 pub mod runtimes;
+pub mod signer;
+pub mod tx;
+pub mod upgrade;
+pub mod uptime;
+pub mod validate;
+
+pub use error::Error;
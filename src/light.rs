@@ -0,0 +1,427 @@
+use crate::error::Error;
+use futures::{channel::mpsc, Stream, StreamExt};
+use serde::Deserialize;
+use smoldot_light::{
+    platform::default::DefaultPlatform, AddChainConfig, AddChainConfigJsonRpc, ChainId,
+    JsonRpcResponses,
+};
+use std::collections::HashMap;
+use std::iter;
+use std::num::NonZeroU32;
+use std::pin::Pin;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, Weak,
+};
+use std::task::{Context, Poll};
+use subxt::{
+    error::RpcError,
+    rpc::{RawValue, RpcClientT, RpcFuture, RpcSubscription},
+};
+use tokio::sync::oneshot;
+
+const MAX_PENDING_REQUESTS: u32 = 128;
+const MAX_SUBSCRIPTIONS: u32 = 1024;
+
+type Subscriber = mpsc::UnboundedSender<Result<Box<RawValue>, RpcError>>;
+
+// LightRpc serves the requests of the client from an embedded smoldot light client.
+// It syncs the chain from its peers and checks the storage it reads against the
+// proofs it gets from them, so no single node has to be trusted.
+#[derive(Clone)]
+pub struct LightRpc {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: Mutex<smoldot_light::Client<Arc<DefaultPlatform>>>,
+    chain: ChainId,
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, Pending>>,
+    subscriptions: Mutex<HashMap<String, Subscriber>>,
+}
+
+enum Pending {
+    Request(oneshot::Sender<Result<Box<RawValue>, RpcError>>),
+    // the subscriber is registered under the subscription id once it is known, so
+    // no notification sent right after the response is lost
+    Subscription(oneshot::Sender<Result<Box<RawValue>, RpcError>>, Subscriber),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("light client error: {0}")]
+pub struct LightClientError(String);
+
+impl LightRpc {
+    // Starts a light client for the chain described by the chain spec, as found in the
+    // `chainSpec` file of the network. It has to contain the bootnodes to sync from and
+    // either the genesis storage or a checkpoint.
+    pub fn new(chain_spec: &str) -> Result<Self, Error> {
+        let platform = DefaultPlatform::new(
+            env!("CARGO_PKG_NAME").into(),
+            env!("CARGO_PKG_VERSION").into(),
+        );
+        let mut client = smoldot_light::Client::new(platform);
+
+        let chain = client
+            .add_chain(AddChainConfig {
+                user_data: (),
+                specification: chain_spec,
+                database_content: "",
+                potential_relay_chains: iter::empty(),
+                json_rpc: AddChainConfigJsonRpc::Enabled {
+                    max_pending_requests: NonZeroU32::new(MAX_PENDING_REQUESTS)
+                        .expect("is not zero"),
+                    max_subscriptions: MAX_SUBSCRIPTIONS,
+                },
+            })
+            .map_err(|err| Error::Rpc(client_error(err.to_string())))?;
+
+        let inner = Arc::new(Inner {
+            client: Mutex::new(client),
+            chain: chain.chain_id,
+            next_id: AtomicU64::new(0),
+            pending: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+        });
+
+        let responses = chain
+            .json_rpc_responses
+            .expect("json rpc is enabled on the chain");
+        tokio::spawn(dispatch(Arc::downgrade(&inner), responses));
+
+        Ok(LightRpc { inner })
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        params: Option<Box<RawValue>>,
+        subscriber: Option<Subscriber>,
+    ) -> Result<Box<RawValue>, RpcError> {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        let pending = match subscriber {
+            Some(subscriber) => Pending::Subscription(tx, subscriber),
+            None => Pending::Request(tx),
+        };
+        self.inner
+            .pending
+            .lock()
+            .expect("lock is not poisoned")
+            .insert(id, pending);
+
+        if let Err(err) = self.inner.request(id, method, params) {
+            self.inner
+                .pending
+                .lock()
+                .expect("lock is not poisoned")
+                .remove(&id);
+            return Err(err);
+        }
+
+        rx.await
+            .map_err(|_| client_error("light client stopped".into()))?
+    }
+}
+
+impl Inner {
+    // queues a request on the light client, its response is routed by `dispatch`
+    fn request(
+        &self,
+        id: u64,
+        method: &str,
+        params: Option<Box<RawValue>>,
+    ) -> Result<(), RpcError> {
+        let request = format!(
+            r#"{{"jsonrpc":"2.0","id":{},"method":{},"params":{}}}"#,
+            id,
+            serde_json::to_string(method).map_err(|err| client_error(err.to_string()))?,
+            params.as_ref().map_or("[]", |params| params.get()),
+        );
+
+        self.client
+            .lock()
+            .expect("lock is not poisoned")
+            .json_rpc_request(request, self.chain)
+            .map_err(|err| client_error(err.to_string()))
+    }
+}
+
+// Subscription yields the notifications of a subscription and unsubscribes from it
+// once it is dropped, so the light client does not keep sending them.
+struct Subscription {
+    inner: Arc<Inner>,
+    id: String,
+    unsub: String,
+    notifications: mpsc::UnboundedReceiver<Result<Box<RawValue>, RpcError>>,
+}
+
+impl Stream for Subscription {
+    type Item = Result<Box<RawValue>, RpcError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.notifications.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.inner
+            .subscriptions
+            .lock()
+            .expect("lock is not poisoned")
+            .remove(&self.id);
+
+        // nobody waits for the response, `dispatch` drops it as it is not pending
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let params = serde_json::to_string(&[&self.id])
+            .ok()
+            .and_then(|params| RawValue::from_string(params).ok());
+        let _ = self.inner.request(id, &self.unsub, params);
+    }
+}
+
+#[derive(Deserialize)]
+struct Message {
+    #[serde(default)]
+    id: Option<u64>,
+    #[serde(default)]
+    result: Option<Box<RawValue>>,
+    #[serde(default)]
+    error: Option<Box<RawValue>>,
+    #[serde(default)]
+    params: Option<Notification>,
+}
+
+#[derive(Deserialize)]
+struct Notification {
+    subscription: String,
+    result: Box<RawValue>,
+}
+
+// routes the responses of the light client to the pending requests and the
+// notifications to their subscription, until the client is dropped
+async fn dispatch(inner: Weak<Inner>, mut responses: JsonRpcResponses) {
+    while let Some(response) = responses.next().await {
+        let inner = match inner.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let message: Message = match serde_json::from_str(&response) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+
+        if let Some(notification) = message.params {
+            let mut subscriptions = inner.subscriptions.lock().expect("lock is not poisoned");
+            let closed = match subscriptions.get(&notification.subscription) {
+                Some(subscriber) => subscriber.unbounded_send(Ok(notification.result)).is_err(),
+                None => false,
+            };
+            if closed {
+                subscriptions.remove(&notification.subscription);
+            }
+            continue;
+        }
+
+        let pending = match message.id.and_then(|id| {
+            inner
+                .pending
+                .lock()
+                .expect("lock is not poisoned")
+                .remove(&id)
+        }) {
+            Some(pending) => pending,
+            None => continue,
+        };
+
+        let result = match (message.result, message.error) {
+            (_, Some(err)) => Err(client_error(err.get().to_string())),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(client_error("empty response".into())),
+        };
+
+        match pending {
+            Pending::Request(tx) => {
+                let _ = tx.send(result);
+            }
+            Pending::Subscription(tx, subscriber) => {
+                if let Ok(Ok(id)) = result
+                    .as_ref()
+                    .map(|id| serde_json::from_str::<String>(id.get()))
+                {
+                    inner
+                        .subscriptions
+                        .lock()
+                        .expect("lock is not poisoned")
+                        .insert(id, subscriber);
+                }
+                let _ = tx.send(result);
+            }
+        }
+    }
+}
+
+impl RpcClientT for LightRpc {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<RawValue>>,
+    ) -> RpcFuture<'a, Box<RawValue>> {
+        Box::pin(self.send(method, params, None))
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<RawValue>>,
+        unsub: &'a str,
+    ) -> RpcFuture<'a, RpcSubscription> {
+        Box::pin(async move {
+            let (tx, rx) = mpsc::unbounded();
+            let id = self.send(sub, params, Some(tx)).await?;
+            let id: String = serde_json::from_str(id.get())
+                .map_err(|_| client_error(format!("invalid subscription id {}", id)))?;
+
+            Ok(Subscription {
+                inner: self.inner.clone(),
+                id,
+                unsub: unsub.into(),
+                notifications: rx,
+            }
+            .boxed())
+        })
+    }
+}
+
+fn client_error(msg: String) -> RpcError {
+    RpcError::ClientError(Box::new(LightClientError(msg)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::{Compact, Encode};
+    use std::time::Duration;
+
+    // babe epoch as stored in the light sync state: index, start slot, duration,
+    // authorities, randomness and the `c` constant with the allowed slots
+    fn epoch(index: u64, slot: u64) -> Vec<u8> {
+        let mut epoch = vec![1];
+        epoch.extend(
+            (
+                index,
+                slot,
+                10u64,
+                vec![([index as u8; 32], 1u64)],
+                [0u8; 32],
+            )
+                .encode(),
+        );
+        epoch.extend((1u64, 4u64, 0u8).encode());
+        epoch
+    }
+
+    // A chain spec for a local chain without bootnodes, it starts from a checkpoint at
+    // block 1 so the light client does not need the genesis runtime.
+    fn local_chain_spec() -> String {
+        let header = (
+            [0u8; 32],
+            Compact(1u32),
+            [1u8; 32],
+            [2u8; 32],
+            Vec::<u8>::new(),
+        );
+
+        let mut epochs = (Vec::<u8>::new(), None::<u32>, Compact(2u32)).encode();
+        epochs.extend(([3u8; 32], 0u32).encode());
+        epochs.extend(epoch(1, 10));
+        epochs.extend(([4u8; 32], 1u32).encode());
+        epochs.extend(epoch(2, 20));
+
+        let authorities = (
+            vec![([5u8; 32], 1u64)],
+            0u64,
+            (Vec::<u8>::new(), None::<u32>),
+            Vec::<u8>::new(),
+            Vec::<(u64, u32)>::new(),
+        );
+
+        serde_json::json!({
+            "name": "Local Testnet",
+            "id": "local_testnet",
+            "chainType": "Local",
+            "bootNodes": [],
+            "telemetryEndpoints": null,
+            "protocolId": null,
+            "properties": null,
+            "forkBlocks": null,
+            "badBlocks": null,
+            "genesis": { "stateRootHash": format!("0x{}", hex::encode([6u8; 32])) },
+            "lightSyncState": {
+                "babeEpochChanges": format!("0x{}", hex::encode(epochs)),
+                "babeFinalizedBlockWeight": 1,
+                "finalizedBlockHeader": format!("0x{}", hex::encode(header.encode())),
+                "grandpaAuthoritySet": format!("0x{}", hex::encode(authorities.encode())),
+            },
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn serves_local_chain() {
+        assert!(LightRpc::new("{}").is_err());
+
+        let rpc = LightRpc::new(&local_chain_spec()).unwrap();
+        let chain = tokio::time::timeout(
+            Duration::from_secs(10),
+            rpc.request_raw("system_chain", None),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(chain.get(), r#""Local Testnet""#);
+
+        let err = rpc.request_raw("no_such_method", None).await.unwrap_err();
+        assert!(matches!(err, RpcError::ClientError(_)));
+    }
+
+    #[tokio::test]
+    async fn dropped_subscriptions_are_unsubscribed() {
+        let rpc = LightRpc::new(&local_chain_spec()).unwrap();
+        let subscription = tokio::time::timeout(
+            Duration::from_secs(10),
+            rpc.subscribe_raw("chain_subscribeNewHeads", None, "chain_unsubscribeNewHeads"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let id = match rpc
+            .inner
+            .subscriptions
+            .lock()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>()[..]
+        {
+            [id] => id.clone(),
+            ref ids => panic!("expected one subscription, got {:?}", ids),
+        };
+
+        drop(subscription);
+        assert!(rpc.inner.subscriptions.lock().unwrap().is_empty());
+
+        // the light client answers `false` once it does not know the subscription anymore
+        let params = RawValue::from_string(serde_json::to_string(&[&id]).unwrap()).unwrap();
+        let unsubscribed = tokio::time::timeout(
+            Duration::from_secs(10),
+            rpc.request_raw("chain_unsubscribeNewHeads", Some(params)),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(unsubscribed.get(), "false");
+    }
+}
@@ -75,9 +75,24 @@
 // }
 
 pub mod client;
+pub mod error;
+pub mod http;
+pub mod keystore;
+#[cfg(feature = "light-client")]
+pub mod light;
+pub mod nonce;
+pub mod proof;
+pub mod retry;
+pub mod rpc;
 pub mod runtimes;
+pub mod signer;
+pub mod tx;
+pub mod upgrade;
+pub mod uptime;
+pub mod validate;
 
-use client::{KeyPair, KeyType, Runtime};
+use client::{KeyPair, KeyType, TwinSpec};
+use tx::TxOptions;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -88,25 +103,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let seed = "0x9917ea107aca8e9c29f4530413b41333ada03cf39fede45cde611b943e2e8dd1";
     let _ = KeyPair::from_phrase(KeyType::Sr25519, seed, None);
 
-    // let cl = client::Client::new(
-    //     String::from("wss://tfchain.dev.grid.tf:443"),
-    //     Runtime::Devnet,
-    // )
-    // .await?;
+    // let cl = client::Client::connect(String::from("wss://tfchain.dev.grid.tf:443")).await?;
 
-    let cl = client::Client::new(String::from("ws://localhost:9944"), Runtime::Local).await?;
+    let cl = client::Client::connect(String::from("ws://localhost:9944")).await?;
 
     let twin = cl.get_twin_by_id(1, None).await?;
     println!("got twin: {:?}", twin);
 
     println!("trying to submit tand call");
-    let hash = cl
-        .sign_terms_and_conditions(&p, String::from("some"), String::from("some"))
+    let opts = TxOptions::default();
+    let receipt = cl
+        .sign_terms_and_conditions(&p, String::from("some"), String::from("some"), &opts)
         .await?;
 
-    println!("tandc call executed with hash {:?}", hash);
+    println!("tandc call executed with hash {:?}", receipt.block_hash);
 
-    let twin_id = cl.create_twin(&p, Some(String::from("::1")), None).await?;
+    let twin_id = cl
+        .create_twin(
+            &p,
+            TwinSpec::with_relay(Some(String::from("::1")), None),
+            &opts,
+        )
+        .await?;
 
     println!("twin created with id {:?}", twin_id);
 
@@ -0,0 +1,282 @@
+use crate::error::Error;
+use std::collections::HashMap;
+use std::sync::{self, Arc};
+use subxt::{error::RpcError, ext::sp_runtime::AccountId32, OnlineClient, PolkadotConfig};
+use tokio::sync::Mutex;
+
+// NonceManager hands out the nonces of the accounts submitting extrinsics. Nonces
+// are allocated locally so one account can have many extrinsics in flight at once.
+// The next nonce of an account is read from the node the first time it is used and
+// every time it is resynced.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    // every account has its own lock, reading the nonce of one account from the node
+    // does not hold up the others
+    nonces: sync::Mutex<HashMap<AccountId32, Arc<Mutex<AccountNonce>>>>,
+}
+
+#[derive(Debug, Default)]
+struct AccountNonce {
+    // the next nonce to allocate, `None` until it is read from the node
+    next: Option<u32>,
+    // the allocated nonces whose extrinsic is not done yet. The node does not know
+    // about them, so the nonce cannot be read again from the node while there are any.
+    in_flight: usize,
+    // an extrinsic failed and its nonce may not be used, the nonce is read again
+    // from the node once no extrinsics are in flight
+    stale: bool,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn account(&self, account: &AccountId32) -> Arc<Mutex<AccountNonce>> {
+        self.nonces
+            .lock()
+            .expect("lock is not poisoned")
+            .entry(account.clone())
+            .or_default()
+            .clone()
+    }
+
+    // allocates the next nonce of the account, `done` must be called once the
+    // extrinsic using it is submitted or failed
+    pub async fn next(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        account: &AccountId32,
+    ) -> Result<u32, Error> {
+        let local = self.account(account);
+        let mut local = local.lock().await;
+        let nonce = match local.next {
+            Some(nonce) => nonce,
+            None => api.rpc().system_account_next_index(account).await?,
+        };
+        local.next = Some(nonce + 1);
+        local.in_flight += 1;
+
+        Ok(nonce)
+    }
+
+    // releases a nonce allocated with `next`. A `failed` extrinsic may have left its
+    // nonce unused, so the local nonce is dropped and read again from the node, but
+    // only once the other allocated nonces are done. Reading it earlier would hand
+    // out the nonces of the extrinsics the node does not know about yet again.
+    pub async fn done(&self, account: &AccountId32, failed: bool) {
+        let local = self.account(account);
+        let mut local = local.lock().await;
+        local.in_flight = local.in_flight.saturating_sub(1);
+        local.stale |= failed;
+
+        if local.stale && local.in_flight == 0 {
+            local.next = None;
+            local.stale = false;
+        }
+    }
+
+    // reads the next nonce of the account from the node, dropping the local one.
+    // The node also counts the extrinsics of the account waiting in its pool.
+    pub async fn resync(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        account: &AccountId32,
+    ) -> Result<u32, Error> {
+        let local = self.account(account);
+        let mut local = local.lock().await;
+        let nonce = api.rpc().system_account_next_index(account).await?;
+        local.next = Some(nonce);
+        local.stale = false;
+
+        Ok(nonce)
+    }
+}
+
+// the transaction pool rejects extrinsics whose nonce is already used (stale) or
+// is ahead of the account nonce (future), and replacements of an extrinsic with the
+// same nonce that do not pay a higher priority
+const NONCE_ERRORS: &[&str] = &[
+    "Transaction is outdated",
+    "Transaction will be valid in the future",
+    "Priority is too low",
+];
+
+// reports if the node rejected an extrinsic because of its nonce
+pub(crate) fn is_nonce_error(err: &Error) -> bool {
+    match err {
+        // the error returned by the node is only found in the wrapped client error
+        Error::Rpc(RpcError::ClientError(err)) => {
+            let err = err.to_string();
+            NONCE_ERRORS.iter().any(|msg| err.contains(msg))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtimes::testing;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    use subxt::rpc::{RawValue, RpcClientT, RpcFuture, RpcSubscription};
+
+    // answers the requests needed to build a client, the next index of `slow` is
+    // never returned
+    struct MockRpc {
+        slow: AccountId32,
+        // the next index of the other accounts
+        index: AtomicU32,
+    }
+
+    impl MockRpc {
+        fn new(slow: AccountId32) -> Self {
+            MockRpc {
+                slow,
+                index: AtomicU32::new(5),
+            }
+        }
+    }
+
+    impl RpcClientT for MockRpc {
+        fn request_raw<'a>(
+            &'a self,
+            method: &'a str,
+            params: Option<Box<RawValue>>,
+        ) -> RpcFuture<'a, Box<RawValue>> {
+            Box::pin(async move {
+                if let Some(value) = testing::client_response("devnet", method) {
+                    return Ok(RawValue::from_string(value.to_string()).expect("value is json"));
+                }
+
+                let value = match method {
+                    "system_accountNextIndex" => {
+                        let params = params.map(|p| p.get().to_string()).unwrap_or_default();
+                        if params.contains(&self.slow.to_string()) {
+                            futures::future::pending::<()>().await;
+                        }
+                        serde_json::json!(self.index.load(Ordering::SeqCst))
+                    }
+                    method => panic!("unexpected request {}", method),
+                };
+
+                Ok(RawValue::from_string(value.to_string()).expect("value is json"))
+            })
+        }
+
+        fn subscribe_raw<'a>(
+            &'a self,
+            _sub: &'a str,
+            _params: Option<Box<RawValue>>,
+            _unsub: &'a str,
+        ) -> RpcFuture<'a, RpcSubscription> {
+            unimplemented!("no subscriptions are made")
+        }
+    }
+
+    #[tokio::test]
+    async fn accounts_are_locked_separately() {
+        let slow = AccountId32::new([1; 32]);
+        let account = AccountId32::new([2; 32]);
+        let api =
+            OnlineClient::<PolkadotConfig>::from_rpc_client(Arc::new(MockRpc::new(slow.clone())))
+                .await
+                .unwrap();
+        let nonces = Arc::new(NonceManager::new());
+
+        let pending = tokio::spawn({
+            let (api, nonces) = (api.clone(), nonces.clone());
+            async move { nonces.next(&api, &slow).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let next = |nonces: Arc<NonceManager>| {
+            let (api, account) = (api.clone(), account.clone());
+            async move {
+                tokio::time::timeout(Duration::from_secs(1), nonces.next(&api, &account))
+                    .await
+                    .expect("nonce is not held up by the other account")
+                    .unwrap()
+            }
+        };
+        assert_eq!(next(nonces.clone()).await, 5);
+        assert_eq!(next(nonces.clone()).await, 6);
+
+        // the nonce is read again from the node after a failure
+        nonces.done(&account, true).await;
+        nonces.done(&account, false).await;
+        assert_eq!(next(nonces.clone()).await, 5);
+
+        assert!(!pending.is_finished());
+        pending.abort();
+    }
+
+    #[tokio::test]
+    async fn failures_do_not_reset_nonces_in_flight() {
+        let account = AccountId32::new([2; 32]);
+        let rpc = Arc::new(MockRpc::new(AccountId32::new([1; 32])));
+        let api = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc.clone())
+            .await
+            .unwrap();
+        let nonces = NonceManager::new();
+
+        assert_eq!(nonces.next(&api, &account).await.unwrap(), 5);
+        assert_eq!(nonces.next(&api, &account).await.unwrap(), 6);
+
+        // 5 failed while 6 is not known to the node yet, reading the nonce from the
+        // node would hand out 5 and 6 again
+        nonces.done(&account, true).await;
+        assert_eq!(nonces.next(&api, &account).await.unwrap(), 7);
+        nonces.done(&account, false).await;
+
+        // the nonce is read from the node once nothing is in flight
+        rpc.index.store(6, Ordering::SeqCst);
+        nonces.done(&account, false).await;
+        assert_eq!(nonces.next(&api, &account).await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn stale_nonces_are_resynced() {
+        let account = AccountId32::new([2; 32]);
+        let rpc = Arc::new(MockRpc::new(AccountId32::new([1; 32])));
+        let api = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc.clone())
+            .await
+            .unwrap();
+        let nonces = NonceManager::new();
+
+        assert_eq!(nonces.next(&api, &account).await.unwrap(), 5);
+        assert_eq!(nonces.next(&api, &account).await.unwrap(), 6);
+
+        // another client used the account, the node is ahead of the local nonce
+        rpc.index.store(9, Ordering::SeqCst);
+        nonces.done(&account, false).await;
+        assert_eq!(nonces.resync(&api, &account).await.unwrap(), 9);
+        assert_eq!(nonces.next(&api, &account).await.unwrap(), 9);
+        assert_eq!(nonces.next(&api, &account).await.unwrap(), 10);
+    }
+
+    fn client_error(msg: &str) -> Error {
+        Error::Rpc(RpcError::ClientError(msg.to_string().into()))
+    }
+
+    #[test]
+    fn nonce_errors_are_recognized() {
+        for msg in [
+            "Invalid Transaction (1010): Transaction is outdated",
+            "Transaction will be valid in the future",
+            "Priority is too low: (140 vs 140)",
+        ] {
+            assert!(is_nonce_error(&client_error(msg)), "{}", msg);
+        }
+
+        assert!(!is_nonce_error(&client_error(
+            "Inability to pay some fees (e.g. account balance too low)"
+        )));
+        // only the errors returned by the node are nonce errors
+        assert!(!is_nonce_error(&Error::Signer(
+            "Transaction is outdated".into()
+        )));
+        assert!(!is_nonce_error(&Error::Rpc(RpcError::SubscriptionDropped)));
+    }
+}
@@ -0,0 +1,375 @@
+use crate::error::Error;
+use crate::runtimes::{
+    dynamic,
+    types::{Contract, Hash, Header, SystemAccountInfo, TfgridFarm, TfgridNode, Twin},
+};
+use serde::{Deserialize, Serialize};
+use sp_trie::{read_trie_value, LayoutV1, StorageProof as TrieProof};
+use subxt::{
+    dynamic::Value,
+    ext::sp_runtime::{traits::BlakeTwo256, AccountId32},
+    metadata::Metadata,
+    storage::utils::storage_address_bytes,
+};
+
+// StorageProof is the evidence that an entry was in the state of a block. `raw` is the
+// SCALE encoded entry at `key` as stored on chain, it is what the proof nodes prove
+// against `state_root`. It only holds data so it can be saved and checked later, by
+// someone who does not trust the node it was read from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageProof {
+    pub raw: Option<Vec<u8>>,
+    pub key: Vec<u8>,
+    pub block_hash: Hash,
+    pub state_root: Hash,
+    pub proof: Vec<Vec<u8>>,
+}
+
+// Proven is a value read from the chain with the proof of the entry it is decoded from
+#[derive(Debug, Clone)]
+pub struct Proven<T> {
+    pub value: T,
+    pub proof: StorageProof,
+}
+
+impl<T: PartialEq> Proven<T> {
+    // Checks the proof of the entry like `verify_proof` and that the value is the one
+    // decoded from the proven entry.
+    pub fn verify<E: ProvenEntry<Value = T>>(
+        &self,
+        header: &Header,
+        metadata: &Metadata,
+        entry: &E,
+    ) -> Result<(), Error> {
+        if verify_proof(header, metadata, entry, &self.proof)? != self.value {
+            return Err(Error::InvalidProof(
+                "value does not match the proven entry".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// ProvenEntry is a storage entry whose value can be proven. It gives the key of the
+// entry and decodes its value with the metadata only, like `Runtime::Dynamic` does,
+// so a proof can be checked offline whatever runtime the client was generated for.
+pub trait ProvenEntry {
+    type Value: PartialEq;
+
+    fn key(&self, metadata: &Metadata) -> Result<Vec<u8>, Error>;
+
+    // `None` is for an entry that does not exist
+    fn decode(metadata: &Metadata, raw: Option<&[u8]>) -> Result<Self::Value, Error>;
+}
+
+fn entry_key(metadata: &Metadata, pallet: &str, entry: &str, key: Value) -> Result<Vec<u8>, Error> {
+    let address = subxt::dynamic::storage(pallet, entry, vec![key]);
+    Ok(storage_address_bytes(&address, metadata)?)
+}
+
+macro_rules! proven_entry {
+    ($name:ident($key:ty), $value:ty, $pallet:literal, $entry:literal, $to_value:expr, $decode:path) => {
+        pub struct $name(pub $key);
+
+        impl ProvenEntry for $name {
+            type Value = Option<$value>;
+
+            fn key(&self, metadata: &Metadata) -> Result<Vec<u8>, Error> {
+                let to_value: fn(&$key) -> Value = $to_value;
+                entry_key(metadata, $pallet, $entry, to_value(&self.0))
+            }
+
+            fn decode(metadata: &Metadata, raw: Option<&[u8]>) -> Result<Self::Value, Error> {
+                $decode(metadata, raw)
+            }
+        }
+    };
+}
+
+// the entries proven by the `*_with_proof` getters of the client
+proven_entry!(
+    TwinById(u32),
+    Twin,
+    "TfgridModule",
+    "Twins",
+    |id| Value::u128((*id).into()),
+    dynamic::twin_from_raw
+);
+proven_entry!(
+    TwinIdByAccount(AccountId32),
+    u32,
+    "TfgridModule",
+    "TwinIdByAccountID",
+    |account| Value::from_bytes(account),
+    dynamic::twin_id_from_raw
+);
+proven_entry!(
+    FarmById(u32),
+    TfgridFarm,
+    "TfgridModule",
+    "Farms",
+    |id| Value::u128((*id).into()),
+    dynamic::farm_from_raw
+);
+// the power of a node is kept in its own entry, which is not proven, the node is up
+proven_entry!(
+    NodeById(u32),
+    TfgridNode,
+    "TfgridModule",
+    "Nodes",
+    |id| Value::u128((*id).into()),
+    dynamic::node_from_raw
+);
+proven_entry!(
+    ContractById(u64),
+    Contract,
+    "SmartContractModule",
+    "Contracts",
+    |id| Value::u128((*id).into()),
+    dynamic::contract_from_raw
+);
+proven_entry!(
+    Balance(AccountId32),
+    SystemAccountInfo,
+    "System",
+    "Account",
+    |account| Value::from_bytes(account),
+    dynamic::account_info_from_raw
+);
+
+// Checks the proof against a header known to be part of the chain, for example one
+// taken from a finalized block, and returns the value of the entry it proves. The
+// proof has to be for the entry that was asked for, a valid proof of another entry is
+// rejected. Nothing is requested from a node, the metadata of the runtime of the
+// block is only needed for the key and the value of the entry.
+pub fn verify_proof<E: ProvenEntry>(
+    header: &Header,
+    metadata: &Metadata,
+    entry: &E,
+    proof: &StorageProof,
+) -> Result<E::Value, Error> {
+    if header.hash() != proof.block_hash {
+        return Err(Error::InvalidProof(format!(
+            "header {:?} is not the one of block {:?}",
+            header.hash(),
+            proof.block_hash
+        )));
+    }
+
+    if header.state_root != proof.state_root {
+        return Err(Error::InvalidProof(
+            "state root does not match the header".into(),
+        ));
+    }
+
+    if entry.key(metadata)? != proof.key {
+        return Err(Error::InvalidProof(
+            "proof is not for the requested entry".into(),
+        ));
+    }
+
+    let raw = verify_storage(&header.state_root, &proof.key, &proof.proof)?;
+    if raw != proof.raw {
+        return Err(Error::InvalidProof(
+            "raw entry does not match the proven entry".into(),
+        ));
+    }
+
+    E::decode(metadata, raw.as_deref())
+        .map_err(|err| Error::InvalidProof(format!("cannot decode the proven entry: {}", err)))
+}
+
+// Reads the entry at the key from the proof nodes, failing if they do not prove it
+// against the state root. `None` is proof that the entry does not exist.
+pub fn verify_storage(
+    state_root: &Hash,
+    key: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, Error> {
+    let db = TrieProof::new(proof.iter().cloned()).into_memory_db::<BlakeTwo256>();
+
+    read_trie_value::<LayoutV1<BlakeTwo256>, _>(&db, state_root, key, None, None)
+        .map_err(|err| Error::InvalidProof(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtimes::testing;
+    use pallet_balances::AccountData;
+    use sp_trie::{MemoryDB, TrieDBMutBuilder, TrieMut};
+    use subxt::ext::codec::Encode;
+
+    fn account(free: u128) -> SystemAccountInfo {
+        SystemAccountInfo {
+            nonce: 3,
+            data: AccountData {
+                free,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn balance(account: [u8; 32]) -> Balance {
+        Balance(AccountId32::from(account))
+    }
+
+    // a state holding the entries, with all its nodes as the proof
+    fn state(entries: &[(&[u8], &[u8])]) -> (Header, Vec<Vec<u8>>) {
+        let mut db = MemoryDB::<BlakeTwo256>::default();
+        let mut root = Hash::default();
+        {
+            let mut trie =
+                TrieDBMutBuilder::<LayoutV1<BlakeTwo256>>::new(&mut db, &mut root).build();
+            for (key, value) in entries {
+                trie.insert(key, value).unwrap();
+            }
+        }
+
+        let proof = db
+            .drain()
+            .into_values()
+            .filter(|(_, rc)| *rc > 0)
+            .map(|(node, _)| node)
+            .collect();
+        let header = Header {
+            parent_hash: Default::default(),
+            number: 1,
+            state_root: root,
+            extrinsics_root: Default::default(),
+            digest: Default::default(),
+        };
+
+        (header, proof)
+    }
+
+    // proves the entry like `Client::with_proof` does
+    fn prove<E: ProvenEntry>(
+        metadata: &Metadata,
+        header: &Header,
+        nodes: &[Vec<u8>],
+        entry: &E,
+    ) -> Proven<E::Value> {
+        let key = entry.key(metadata).unwrap();
+        let raw = verify_storage(&header.state_root, &key, nodes).unwrap();
+
+        Proven {
+            value: E::decode(metadata, raw.as_deref()).unwrap(),
+            proof: StorageProof {
+                raw,
+                key,
+                block_hash: header.hash(),
+                state_root: header.state_root,
+                proof: nodes.to_vec(),
+            },
+        }
+    }
+
+    fn is_invalid<T>(result: Result<T, Error>) -> bool {
+        matches!(result, Err(Error::InvalidProof(_)))
+    }
+
+    #[test]
+    fn verifies_proven_entries() {
+        let metadata = testing::metadata("devnet");
+        let info = account(1_000_000);
+        let key = balance([1; 32]).key(&metadata).unwrap();
+        let (header, nodes) = state(&[(&key, &info.encode())]);
+
+        let proven = prove(&metadata, &header, &nodes, &balance([1; 32]));
+        assert_eq!(proven.value, Some(info.clone()));
+        proven
+            .verify(&header, &metadata, &balance([1; 32]))
+            .unwrap();
+        assert_eq!(
+            verify_proof(&header, &metadata, &balance([1; 32]), &proven.proof).unwrap(),
+            Some(info)
+        );
+
+        // an account without entry is proven to not exist
+        let missing = prove(&metadata, &header, &nodes, &balance([2; 32]));
+        assert_eq!(missing.value, None);
+        missing
+            .verify(&header, &metadata, &balance([2; 32]))
+            .unwrap();
+
+        let mut other = header.clone();
+        other.number = 2;
+        assert!(is_invalid(proven.verify(
+            &other,
+            &metadata,
+            &balance([1; 32])
+        )));
+    }
+
+    #[test]
+    fn rejects_proofs_of_other_entries() {
+        let metadata = testing::metadata("devnet");
+        let key = balance([2; 32]).key(&metadata).unwrap();
+        let (header, nodes) = state(&[(&key, &account(1_000_000).encode())]);
+
+        let proven = prove(&metadata, &header, &nodes, &balance([2; 32]));
+        proven
+            .verify(&header, &metadata, &balance([2; 32]))
+            .unwrap();
+
+        // the proof is valid, but not for the balance of the account asked for
+        assert!(is_invalid(verify_proof(
+            &header,
+            &metadata,
+            &balance([1; 32]),
+            &proven.proof
+        )));
+        assert!(is_invalid(proven.verify(
+            &header,
+            &metadata,
+            &balance([1; 32])
+        )));
+    }
+
+    #[test]
+    fn rejects_tampered_values() {
+        let metadata = testing::metadata("devnet");
+        let mut info = account(1_000_000);
+        let key = balance([1; 32]).key(&metadata).unwrap();
+        let (header, nodes) = state(&[(&key, &info.encode())]);
+        let proven = prove(&metadata, &header, &nodes, &balance([1; 32]));
+        let verify = |proven: &Proven<_>| proven.verify(&header, &metadata, &balance([1; 32]));
+
+        let mut tampered = proven.clone();
+        tampered.value.as_mut().unwrap().data.free += 1;
+        assert!(is_invalid(verify(&tampered)));
+
+        let mut tampered = proven.clone();
+        tampered.value = None;
+        assert!(is_invalid(verify(&tampered)));
+
+        let mut tampered = proven.clone();
+        info.data.free += 1;
+        tampered.proof.raw = Some(info.encode());
+        tampered.value = Some(info);
+        assert!(is_invalid(verify(&tampered)));
+
+        let mut tampered = proven;
+        tampered.proof.proof.clear();
+        assert!(is_invalid(verify(&tampered)));
+    }
+
+    #[test]
+    fn proofs_can_be_saved() {
+        let metadata = testing::metadata("devnet");
+        let key = balance([1; 32]).key(&metadata).unwrap();
+        let (header, nodes) = state(&[(&key, &account(1_000_000).encode())]);
+        let proven = prove(&metadata, &header, &nodes, &balance([1; 32]));
+
+        let saved = serde_json::to_string(&proven.proof).unwrap();
+        let proof: StorageProof = serde_json::from_str(&saved).unwrap();
+        assert_eq!(proof, proven.proof);
+        assert_eq!(
+            verify_proof(&header, &metadata, &balance([1; 32]), &proof).unwrap(),
+            proven.value
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use crate::error::Error;
+use crate::rpc::ConnectionError;
+use jsonrpsee::{core::Error as JsonRpcError, types::error::CallError};
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+use subxt::error::RpcError;
+
+// ErrorClass groups the transient errors a request can be retried on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    // the connection to the node was lost or could not be established
+    Connection,
+    // the node did not answer in time
+    Timeout,
+    // the node failed to handle the request, for example because it is overloaded
+    Server,
+}
+
+impl ErrorClass {
+    // the class of the error, `None` if retrying cannot help
+    pub fn of(err: &Error) -> Option<Self> {
+        let err = match err {
+            Error::Rpc(RpcError::SubscriptionDropped) => return Some(ErrorClass::Connection),
+            Error::Rpc(RpcError::ClientError(err)) => err,
+            _ => return None,
+        };
+
+        if err.downcast_ref::<ConnectionError>().is_some() {
+            return Some(ErrorClass::Connection);
+        }
+
+        match err.downcast_ref::<JsonRpcError>()? {
+            JsonRpcError::Transport(_)
+            | JsonRpcError::RestartNeeded(_)
+            | JsonRpcError::Internal(_) => Some(ErrorClass::Connection),
+            JsonRpcError::RequestTimeout => Some(ErrorClass::Timeout),
+            // internal errors and the range reserved for implementation defined server errors
+            JsonRpcError::Call(CallError::Custom(err))
+                if err.code() == -32603 || (-32099..=-32000).contains(&err.code()) =>
+            {
+                Some(ErrorClass::Server)
+            }
+            _ => None,
+        }
+    }
+}
+
+// RetryPolicy sets how the client retries requests failing with a transient error.
+// Attempts are spaced by an exponential backoff, randomized by the jitter.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    // number of attempts including the first one, 1 disables retries
+    pub max_attempts: u32,
+    // delay before the first retry, it is multiplied by `multiplier` at every retry
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: u32,
+    // fraction of the backoff that is randomly added or removed, between 0 and 1
+    pub jitter: f64,
+    pub retry_on: Vec<ErrorClass>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2,
+            jitter: 0.2,
+            retry_on: vec![
+                ErrorClass::Connection,
+                ErrorClass::Timeout,
+                ErrorClass::Server,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    // a policy that never retries
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    // whether the failed attempt, counting from 1, should be retried
+    pub fn should_retry(&self, err: &Error, attempt: u32) -> bool {
+        attempt < self.max_attempts
+            && ErrorClass::of(err).is_some_and(|class| self.retry_on.contains(&class))
+    }
+
+    // the delay before retrying the failed attempt, counting from 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return backoff;
+        }
+
+        backoff.mul_f64(rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter))
+    }
+
+    // runs the request until it succeeds, fails with an error that is not retried,
+    // or runs out of attempts
+    pub async fn run<T, F, Fut>(&self, mut request: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match request().await {
+                Err(err) if self.should_retry(&err, attempt) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TfgridError;
+    use jsonrpsee::types::error::ErrorObject;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn rpc_error(err: JsonRpcError) -> Error {
+        Error::Rpc(RpcError::ClientError(Box::new(err)))
+    }
+
+    fn call_error(code: i32) -> Error {
+        rpc_error(JsonRpcError::Call(CallError::Custom(ErrorObject::owned(
+            code, "error", None::<()>,
+        ))))
+    }
+
+    #[test]
+    fn classifies_errors() {
+        let cases = [
+            (
+                Error::Rpc(RpcError::SubscriptionDropped),
+                Some(ErrorClass::Connection),
+            ),
+            (
+                Error::Rpc(RpcError::ClientError(Box::new(
+                    ConnectionError::NoHealthyEndpoint,
+                ))),
+                Some(ErrorClass::Connection),
+            ),
+            (
+                rpc_error(JsonRpcError::RestartNeeded("closed".into())),
+                Some(ErrorClass::Connection),
+            ),
+            (
+                rpc_error(JsonRpcError::RequestTimeout),
+                Some(ErrorClass::Timeout),
+            ),
+            (call_error(-32603), Some(ErrorClass::Server)),
+            (call_error(-32000), Some(ErrorClass::Server)),
+            (call_error(-32099), Some(ErrorClass::Server)),
+            // invalid params and invalid transactions are not transient
+            (call_error(-32602), None),
+            (call_error(1010), None),
+            (Error::Tfgrid(TfgridError::TwinNotExists), None),
+            (Error::Timeout(Duration::from_secs(1)), None),
+        ];
+
+        for (err, class) in cases {
+            assert_eq!(ErrorClass::of(&err), class, "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn should_retry_transient_errors_until_max_attempts() {
+        let policy = RetryPolicy::default();
+        let timeout = rpc_error(JsonRpcError::RequestTimeout);
+
+        assert!(policy.should_retry(&timeout, 1));
+        assert!(policy.should_retry(&timeout, 2));
+        assert!(!policy.should_retry(&timeout, 3));
+        assert!(!policy.should_retry(&call_error(-32602), 1));
+
+        let policy = RetryPolicy {
+            retry_on: vec![ErrorClass::Connection],
+            ..Default::default()
+        };
+        assert!(!policy.should_retry(&timeout, 1));
+        assert!(policy.should_retry(&Error::Rpc(RpcError::SubscriptionDropped), 1));
+
+        assert!(!RetryPolicy::none().should_retry(&timeout, 1));
+    }
+
+    #[test]
+    fn backoff_grows_up_to_max() {
+        let policy = RetryPolicy {
+            jitter: 0.0,
+            ..Default::default()
+        };
+        let backoffs: Vec<_> = (1..=7).map(|attempt| policy.backoff(attempt)).collect();
+        assert_eq!(
+            backoffs,
+            [200, 400, 800, 1600, 3200, 5000, 5000].map(Duration::from_millis)
+        );
+        assert_eq!(policy.backoff(u32::MAX), policy.max_backoff);
+
+        let policy = RetryPolicy::default();
+        for attempt in 1..=10 {
+            let expected = policy.initial_backoff * 2u32.pow(attempt - 1);
+            let expected = expected.min(policy.max_backoff);
+            let backoff = policy.backoff(attempt);
+            assert!(backoff >= expected.mul_f64(0.8) && backoff <= expected.mul_f64(1.2));
+        }
+    }
+
+    #[tokio::test]
+    async fn run_stops_on_success_or_permanent_errors() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let attempts = AtomicU32::new(0);
+
+        let result = policy
+            .run(|| async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(rpc_error(JsonRpcError::RequestTimeout)),
+                    n => Ok(n),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 1);
+
+        attempts.store(0, Ordering::SeqCst);
+        let result: Result<(), _> = policy
+            .run(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(rpc_error(JsonRpcError::RequestTimeout))
+            })
+            .await;
+        assert!(matches!(result, Err(Error::Rpc(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), policy.max_attempts);
+
+        attempts.store(0, Ordering::SeqCst);
+        let result: Result<(), _> = policy
+            .run(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(Error::Tfgrid(TfgridError::TwinNotExists))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,619 @@
+use crate::error::Error;
+use futures::{future, stream, StreamExt};
+use jsonrpsee::{
+    client_transport::ws::{Uri, WsTransportClientBuilder},
+    core::{
+        client::{Client as WsClient, ClientBuilder, ClientT, Subscription, SubscriptionClientT},
+        traits::ToRpcParams,
+        Error as JsonRpcError,
+    },
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{
+    self,
+    atomic::{AtomicU64, Ordering},
+    Arc, Weak,
+};
+use std::time::Duration;
+use subxt::{
+    error::RpcError,
+    rpc::{RawValue, RpcClientT, RpcFuture, RpcSubscription},
+};
+use tokio::sync::{broadcast, mpsc, watch, Mutex, RwLock};
+
+// delay between two attempts to re-establish a subscription while no endpoint is healthy
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+// RpcOptions configures how the endpoints of a `ReconnectingRpc` are checked and picked
+#[derive(Debug, Clone)]
+pub struct RpcOptions {
+    // an endpoint whose best block is more than `max_lag` blocks behind the best
+    // block of the other endpoints is not used
+    pub max_lag: u32,
+    // how often the health of the endpoint in use is checked
+    pub health_check_interval: Duration,
+    // how long connecting to an endpoint and checking its health may take
+    pub connect_timeout: Duration,
+}
+
+impl Default for RpcOptions {
+    fn default() -> Self {
+        RpcOptions {
+            max_lag: 5,
+            health_check_interval: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+// ConnectionEvent is emitted every time a `ReconnectingRpc` connects to or
+// disconnects from an endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    Connected(String),
+    Disconnected(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectionError {
+    #[error("none of the endpoints is healthy")]
+    NoHealthyEndpoint,
+}
+
+// ReconnectingRpc is an rpc client that spreads over a list of websocket endpoints.
+// It uses the first endpoint, in the order given, that is synced and not lagging
+// behind the others. When the connection is lost or the endpoint becomes unhealthy
+// it fails over to another endpoint: pending requests are sent again and
+// subscriptions are re-established on the new endpoint. Requests and subscriptions
+// submitting an extrinsic are not sent again as the extrinsic may already have
+// reached the node. The health checks keep one connection open to every endpoint.
+#[derive(Clone)]
+pub struct ReconnectingRpc {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    endpoints: Vec<String>,
+    opts: RpcOptions,
+    current: RwLock<Option<Connection>>,
+    reconnecting: Mutex<()>,
+    generation: AtomicU64,
+    // the generation of the connection in use, subscriptions follow it to the new
+    // endpoint when it changes
+    switched: watch::Sender<u64>,
+    // one client per endpoint, shared by the connection in use and the health checks
+    clients: sync::Mutex<HashMap<String, Arc<WsClient>>>,
+    events: broadcast::Sender<ConnectionEvent>,
+}
+
+#[derive(Clone)]
+struct Connection {
+    url: String,
+    client: Arc<WsClient>,
+    // identifies the connection, to tell if it was already replaced
+    generation: u64,
+}
+
+impl ReconnectingRpc {
+    // connects to the best of the endpoints, failing if none is healthy
+    pub async fn connect<U: AsRef<str>>(endpoints: &[U], opts: RpcOptions) -> Result<Self, Error> {
+        let (events, _) = broadcast::channel(16);
+        let inner = Arc::new(Inner {
+            endpoints: endpoints.iter().map(|u| u.as_ref().to_string()).collect(),
+            opts,
+            current: RwLock::new(None),
+            reconnecting: Mutex::new(()),
+            generation: AtomicU64::new(0),
+            switched: watch::channel(0).0,
+            clients: sync::Mutex::new(HashMap::new()),
+            events,
+        });
+
+        inner.reconnect(None).await?;
+        tokio::spawn(health_check(Arc::downgrade(&inner)));
+
+        Ok(ReconnectingRpc { inner })
+    }
+
+    // subscribes to the connection changes
+    pub fn events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.inner.events.subscribe()
+    }
+
+    // the endpoint in use, if connected
+    pub async fn endpoint(&self) -> Option<String> {
+        self.inner
+            .current
+            .read()
+            .await
+            .as_ref()
+            .map(|conn| conn.url.clone())
+    }
+}
+
+impl Inner {
+    // returns the connection in use, connecting to an endpoint if it was lost
+    async fn connection(&self) -> Result<Connection, RpcError> {
+        let failed = match self.current.read().await.as_ref() {
+            Some(conn) if conn.client.is_connected() => return Ok(conn.clone()),
+            Some(conn) => Some(conn.generation),
+            None => None,
+        };
+
+        self.reconnect(failed).await
+    }
+
+    // replaces the failed connection by one to the best endpoint. Callers that saw
+    // the same connection fail share a single reconnection.
+    async fn reconnect(&self, failed: Option<u64>) -> Result<Connection, RpcError> {
+        let _guard = self.reconnecting.lock().await;
+
+        let previous = self.current.write().await.take();
+        if let Some(conn) = previous {
+            if Some(conn.generation) != failed && conn.client.is_connected() {
+                let current = conn.clone();
+                *self.current.write().await = Some(conn);
+                return Ok(current);
+            }
+            let _ = self.events.send(ConnectionEvent::Disconnected(conn.url));
+        }
+
+        let (url, client) = self
+            .select()
+            .await
+            .ok_or_else(|| RpcError::ClientError(Box::new(ConnectionError::NoHealthyEndpoint)))?;
+
+        let conn = Connection {
+            url: url.clone(),
+            client,
+            generation: self.generation.fetch_add(1, Ordering::SeqCst) + 1,
+        };
+        *self.current.write().await = Some(conn.clone());
+        self.switched.send_replace(conn.generation);
+        let _ = self.events.send(ConnectionEvent::Connected(url));
+
+        Ok(conn)
+    }
+
+    // returns the client of the endpoint, connecting to it if it is not connected
+    async fn client(&self, url: &str) -> Option<Arc<WsClient>> {
+        let cached = self
+            .clients
+            .lock()
+            .expect("lock is not poisoned")
+            .get(url)
+            .filter(|client| client.is_connected())
+            .cloned();
+        if cached.is_some() {
+            return cached;
+        }
+
+        let client = Arc::new(self.timeout(connect(url)).await?);
+        self.clients
+            .lock()
+            .expect("lock is not poisoned")
+            .insert(url.to_string(), client.clone());
+
+        Some(client)
+    }
+
+    // checks all the endpoints and keeps the first healthy one that is at most
+    // `max_lag` blocks behind the best of them
+    async fn select(&self) -> Option<(String, Arc<WsClient>)> {
+        let probes = future::join_all(self.endpoints.iter().map(|url| async move {
+            let client = self.client(url).await?;
+            let best = self.timeout(status(&client)).await?;
+
+            Some((url.clone(), client, best))
+        }))
+        .await;
+
+        let healthy: Vec<_> = probes.into_iter().flatten().collect();
+        let best = healthy.iter().map(|(_, _, best)| *best).max()?;
+
+        healthy
+            .into_iter()
+            .find(|(_, _, number)| number.saturating_add(self.opts.max_lag) >= best)
+            .map(|(url, client, _)| (url, client))
+    }
+
+    // checks the connection is still alive, synced and not lagging behind the other endpoints
+    async fn is_healthy(&self, conn: &Connection) -> bool {
+        let number = match self.timeout(status(&conn.client)).await {
+            Some(number) => number,
+            None => return false,
+        };
+
+        let others = future::join_all(self.endpoints.iter().filter(|url| **url != conn.url).map(
+            |url| async move {
+                let client = self.client(url).await?;
+                self.timeout(status(&client)).await
+            },
+        ))
+        .await;
+
+        let best = others.into_iter().flatten().max().unwrap_or(number);
+        number.saturating_add(self.opts.max_lag) >= best
+    }
+
+    async fn timeout<T, F: future::Future<Output = Option<T>>>(&self, f: F) -> Option<T> {
+        tokio::time::timeout(self.opts.connect_timeout, f)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    async fn subscribe(
+        &self,
+        sub: &str,
+        params: &Option<Box<RawValue>>,
+        unsub: &str,
+    ) -> Result<(Connection, Subscription<Box<RawValue>>), RpcError> {
+        let mut conn = self.connection().await?;
+        let mut retries = self.endpoints.len();
+
+        loop {
+            match SubscriptionClientT::subscribe(&*conn.client, sub, Params(params.clone()), unsub)
+                .await
+            {
+                Ok(subscription) => return Ok((conn, subscription)),
+                Err(_) if !conn.client.is_connected() && retries > 0 => {
+                    conn = self.reconnect(Some(conn.generation)).await?;
+                    retries -= 1;
+                }
+                Err(err) => return Err(client_error(err)),
+            }
+        }
+    }
+}
+
+// checks the health of the endpoints in use every `health_check_interval`, failing
+// over to another endpoint if it is not healthy. Stops once the client is dropped.
+async fn health_check(inner: Weak<Inner>) {
+    loop {
+        let interval = match inner.upgrade() {
+            Some(inner) => inner.opts.health_check_interval,
+            None => return,
+        };
+        tokio::time::sleep(interval).await;
+
+        let inner = match inner.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let current = inner.current.read().await.clone();
+        let healthy = match &current {
+            Some(conn) => inner.is_healthy(conn).await,
+            None => false,
+        };
+
+        if !healthy {
+            let _ = inner.reconnect(current.map(|conn| conn.generation)).await;
+        }
+    }
+}
+
+// forwards the notifications of the subscription, re-establishing it on the new
+// connection if the connection is lost or another endpoint is used. The subscription
+// is dropped, which unsubscribes, once the receiver is dropped.
+#[allow(clippy::too_many_arguments)]
+async fn forward(
+    inner: Weak<Inner>,
+    mut switched: watch::Receiver<u64>,
+    mut conn: Connection,
+    mut subscription: Subscription<Box<RawValue>>,
+    tx: mpsc::UnboundedSender<Result<Box<RawValue>, RpcError>>,
+    sub: String,
+    params: Option<Box<RawValue>>,
+    unsub: String,
+) {
+    loop {
+        let moved = loop {
+            if *switched.borrow_and_update() != conn.generation {
+                break true;
+            }
+
+            tokio::select! {
+                item = subscription.next() => match item {
+                    Some(item) => {
+                        if tx.send(item.map_err(client_error)).is_err() {
+                            return;
+                        }
+                    }
+                    None => break false,
+                },
+                changed = switched.changed() => {
+                    // the client was dropped
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+                _ = tx.closed() => return,
+            }
+        };
+
+        // the node ended the subscription
+        if !moved && conn.client.is_connected() {
+            return;
+        }
+
+        loop {
+            let inner = match inner.upgrade() {
+                Some(inner) if !tx.is_closed() => inner,
+                _ => return,
+            };
+
+            let failed = conn.generation;
+            match inner.reconnect(Some(failed)).await {
+                Ok(_) => match inner.subscribe(&sub, &params, &unsub).await {
+                    Ok((c, s)) => {
+                        conn = c;
+                        subscription = s;
+                        break;
+                    }
+                    Err(_) => tokio::time::sleep(RESUBSCRIBE_DELAY).await,
+                },
+                Err(_) => tokio::time::sleep(RESUBSCRIBE_DELAY).await,
+            }
+        }
+    }
+}
+
+impl RpcClientT for ReconnectingRpc {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<RawValue>>,
+    ) -> RpcFuture<'a, Box<RawValue>> {
+        Box::pin(async move {
+            let mut conn = self.inner.connection().await?;
+            let mut retries = self.inner.endpoints.len();
+
+            loop {
+                match ClientT::request(&*conn.client, method, Params(params.clone())).await {
+                    Ok(res) => return Ok(res),
+                    Err(err) if !conn.client.is_connected() => {
+                        if is_submission(method) || retries == 0 {
+                            let _ = self.inner.reconnect(Some(conn.generation)).await;
+                            return Err(client_error(err));
+                        }
+
+                        conn = self.inner.reconnect(Some(conn.generation)).await?;
+                        retries -= 1;
+                    }
+                    Err(err) => return Err(client_error(err)),
+                }
+            }
+        })
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<RawValue>>,
+        unsub: &'a str,
+    ) -> RpcFuture<'a, RpcSubscription> {
+        Box::pin(async move {
+            let (conn, subscription) = self.inner.subscribe(sub, &params, unsub).await?;
+
+            if is_submission(sub) {
+                return Ok(subscription.map(|item| item.map_err(client_error)).boxed());
+            }
+
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(forward(
+                Arc::downgrade(&self.inner),
+                self.inner.switched.subscribe(),
+                conn,
+                subscription,
+                tx,
+                sub.to_string(),
+                params,
+                unsub.to_string(),
+            ));
+
+            Ok(stream::unfold(rx, |mut rx| async move { Some((rx.recv().await?, rx)) }).boxed())
+        })
+    }
+}
+
+// submitting an extrinsic twice is rejected by the node, or worse the second one
+// fails after the first was included
+fn is_submission(method: &str) -> bool {
+    method.starts_with("author_submit")
+}
+
+pub(crate) fn client_error(err: JsonRpcError) -> RpcError {
+    RpcError::ClientError(Box::new(err))
+}
+
+pub(crate) struct Params(pub(crate) Option<Box<RawValue>>);
+
+impl ToRpcParams for Params {
+    fn to_rpc_params(self) -> Result<Option<Box<RawValue>>, JsonRpcError> {
+        Ok(self.0)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Health {
+    peers: u64,
+    is_syncing: bool,
+    should_have_peers: bool,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    number: String,
+}
+
+async fn connect(url: &str) -> Option<WsClient> {
+    let url: Uri = url.parse().ok()?;
+    let (sender, receiver) = WsTransportClientBuilder::default().build(url).await.ok()?;
+
+    Some(
+        ClientBuilder::default()
+            .max_notifs_per_subscription(4096)
+            .build_with_tokio(sender, receiver),
+    )
+}
+
+// returns the best block of the endpoint if it is healthy
+async fn status(client: &WsClient) -> Option<u32> {
+    let health: Health = client.request("system_health", Params(None)).await.ok()?;
+    if health.is_syncing || (health.should_have_peers && health.peers == 0) {
+        return None;
+    }
+
+    let header: Header = client.request("chain_getHeader", Params(None)).await.ok()?;
+    u32::from_str_radix(header.number.trim_start_matches("0x"), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::server::{RpcModule, ServerBuilder, ServerHandle};
+    use std::sync::atomic::{AtomicU32, AtomicUsize};
+    use tokio::net::{TcpListener, TcpStream};
+
+    // a node answering the health checks, its subscription sends its name every 10ms
+    struct Node {
+        name: &'static str,
+        best: AtomicU32,
+        // the websocket connections accepted and the subscriptions not closed yet
+        connections: AtomicUsize,
+        subscriptions: AtomicUsize,
+    }
+
+    impl Node {
+        async fn start(name: &'static str, best: u32) -> (Arc<Node>, String, ServerHandle) {
+            let node = Arc::new(Node {
+                name,
+                best: AtomicU32::new(best),
+                connections: AtomicUsize::new(0),
+                subscriptions: AtomicUsize::new(0),
+            });
+
+            let mut module = RpcModule::new(node.clone());
+            module
+                .register_method("system_health", |_, _| {
+                    Ok(serde_json::json!({"peers": 1, "isSyncing": false, "shouldHavePeers": true}))
+                })
+                .unwrap();
+            module
+                .register_method("chain_getHeader", |_, node| {
+                    let best = node.best.load(Ordering::SeqCst);
+                    Ok(serde_json::json!({ "number": format!("0x{:x}", best) }))
+                })
+                .unwrap();
+            module
+                .register_subscription("test_subscribe", "test_item", "test_unsubscribe", {
+                    |_, mut sink, node| {
+                        sink.accept()?;
+                        node.subscriptions.fetch_add(1, Ordering::SeqCst);
+                        tokio::spawn(async move {
+                            while let Ok(true) = sink.send(&node.name) {
+                                tokio::time::sleep(Duration::from_millis(10)).await;
+                            }
+                            node.subscriptions.fetch_sub(1, Ordering::SeqCst);
+                        });
+                        Ok(())
+                    }
+                })
+                .unwrap();
+
+            let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+            let addr = server.local_addr().unwrap();
+            let handle = server.start(module).unwrap();
+
+            // counts the connections made to the node
+            let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("ws://{}", proxy.local_addr().unwrap());
+            tokio::spawn({
+                let node = node.clone();
+                async move {
+                    while let Ok((mut client, _)) = proxy.accept().await {
+                        node.connections.fetch_add(1, Ordering::SeqCst);
+                        tokio::spawn(async move {
+                            let mut server = TcpStream::connect(addr).await.unwrap();
+                            let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                        });
+                    }
+                }
+            });
+
+            (node, url, handle)
+        }
+    }
+
+    async fn next(items: &mut RpcSubscription) -> String {
+        let item = tokio::time::timeout(Duration::from_secs(5), items.next())
+            .await
+            .expect("notification in time")
+            .unwrap()
+            .unwrap();
+
+        serde_json::from_str(item.get()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn subscriptions_follow_failover() {
+        let (a, a_url, _a) = Node::start("a", 100).await;
+        let (b, b_url, _b) = Node::start("b", 100).await;
+
+        let rpc = ReconnectingRpc::connect(
+            &[a_url, b_url.clone()],
+            RpcOptions {
+                max_lag: 5,
+                health_check_interval: Duration::from_millis(50),
+                connect_timeout: Duration::from_secs(1),
+            },
+        )
+        .await
+        .unwrap();
+        let mut events = rpc.events();
+
+        let mut items = rpc
+            .subscribe_raw("test_subscribe", None, "test_unsubscribe")
+            .await
+            .unwrap();
+        assert_eq!(next(&mut items).await, "a");
+
+        // the health checks reuse the connections to the endpoints
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(a.connections.load(Ordering::SeqCst), 1);
+        assert_eq!(b.connections.load(Ordering::SeqCst), 1);
+
+        // `a` falls behind, the client moves to `b` while `a` is still reachable
+        b.best.store(200, Ordering::SeqCst);
+        let event = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let ConnectionEvent::Connected(url) = events.recv().await.unwrap() {
+                    break url;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(event, b_url);
+
+        // the subscription is moved to `b` and the one on `a` is closed
+        let mut item = next(&mut items).await;
+        while item == "a" {
+            item = next(&mut items).await;
+        }
+        assert_eq!(item, "b");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(a.subscriptions.load(Ordering::SeqCst), 0);
+        assert_eq!(b.subscriptions.load(Ordering::SeqCst), 1);
+
+        // dropping the stream unsubscribes
+        drop(items);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(b.subscriptions.load(Ordering::SeqCst), 0);
+        assert_eq!(b.connections.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,1626 @@
+use super::types::{self, Hash};
+use super::TfchainRuntime;
+use crate::client::Client;
+use crate::error::Error;
+use crate::signer::TfchainSigner;
+use crate::tx::{EncodedCall, TxOptions, TxReceipt, WaitFor};
+use scale_info::TypeDef;
+use std::collections::HashSet;
+use subxt::{
+    dynamic::{DecodedValue, Value},
+    ext::{
+        scale_value::{scale::TypeId, Composite, Primitive, ValueDef},
+        sp_runtime::AccountId32,
+    },
+    metadata::{DecodeWithMetadata, Metadata},
+    tx::TxPayload,
+    OnlineClient, PolkadotConfig,
+};
+
+const TFGRID: &str = "TfgridModule";
+
+// Dynamic is a runtime that is not generated from bundled metadata. Storage and
+// calls are resolved against the metadata the node returns at connect time and
+// the values are mapped by field name, so it keeps working with runtimes that are
+// not in `artifacts/` as long as the fields used by the client are unchanged.
+pub struct Dynamic;
+
+#[async_trait::async_trait]
+impl TfchainRuntime for Dynamic {
+    fn name(&self) -> &'static str {
+        "dynamic"
+    }
+
+    fn metadata(&self) -> Option<&'static [u8]> {
+        None
+    }
+
+    fn validate(&self, _api: &OnlineClient<PolkadotConfig>) -> bool {
+        true
+    }
+
+    fn create_twin_call(&self, cl: &Client, spec: types::TwinSpec) -> Result<EncodedCall, Error> {
+        let fields = twin_fields(&cl.api.metadata(), "create_twin", spec)?;
+
+        call(cl, &subxt::dynamic::tx(TFGRID, "create_twin", fields))
+    }
+
+    fn update_twin_call(&self, cl: &Client, spec: types::TwinSpec) -> Result<EncodedCall, Error> {
+        let fields = twin_fields(&cl.api.metadata(), "update_twin", spec)?;
+
+        call(cl, &subxt::dynamic::tx(TFGRID, "update_twin", fields))
+    }
+
+    fn sign_terms_and_conditions_call(
+        &self,
+        cl: &Client,
+        document_link: String,
+        document_hash: String,
+    ) -> Result<EncodedCall, Error> {
+        let sign_tandc_tx = subxt::dynamic::tx(
+            TFGRID,
+            "user_accept_tc",
+            vec![
+                ("document_link", Value::from_bytes(document_link)),
+                ("document_hash", Value::from_bytes(document_hash)),
+            ],
+        );
+
+        call(cl, &sign_tandc_tx)
+    }
+
+    fn create_farm_call(
+        &self,
+        cl: &Client,
+        name: String,
+        public_ips: Vec<types::FarmPublicIPInput>,
+    ) -> Result<EncodedCall, Error> {
+        let public_ips = public_ips.into_iter().map(|ip| {
+            Value::named_composite([
+                ("ip", Value::from_bytes(ip.ip)),
+                ("gw", Value::from_bytes(ip.gateway)),
+            ])
+        });
+        let create_farm_tx = subxt::dynamic::tx(
+            TFGRID,
+            "create_farm",
+            vec![
+                ("name", Value::from_bytes(name)),
+                // the list is wrapped in its `BoundedVec`, otherwise a single ip
+                // would be taken for the content of the wrapper
+                (
+                    "public_ips",
+                    Value::unnamed_composite([Value::unnamed_composite(public_ips)]),
+                ),
+            ],
+        );
+
+        call(cl, &create_farm_tx)
+    }
+
+    fn update_farm_call(&self, cl: &Client, id: u32, name: String) -> Result<EncodedCall, Error> {
+        let update_farm_tx = subxt::dynamic::tx(
+            TFGRID,
+            "update_farm",
+            vec![
+                ("id", Value::u128(id.into())),
+                ("name", Value::from_bytes(name)),
+            ],
+        );
+
+        call(cl, &update_farm_tx)
+    }
+
+    fn add_farm_ip_call(
+        &self,
+        cl: &Client,
+        id: u32,
+        ip: String,
+        gateway: String,
+    ) -> Result<EncodedCall, Error> {
+        let add_farm_ip_tx = subxt::dynamic::tx(
+            TFGRID,
+            "add_farm_ip",
+            vec![
+                ("id", Value::u128(id.into())),
+                ("ip", Value::from_bytes(ip)),
+                ("gw", Value::from_bytes(gateway)),
+            ],
+        );
+
+        call(cl, &add_farm_ip_tx)
+    }
+
+    fn remove_farm_ip_call(&self, cl: &Client, id: u32, ip: String) -> Result<EncodedCall, Error> {
+        let remove_farm_ip_tx = subxt::dynamic::tx(
+            TFGRID,
+            "remove_farm_ip",
+            vec![
+                ("id", Value::u128(id.into())),
+                ("ip", Value::from_bytes(ip)),
+            ],
+        );
+
+        call(cl, &remove_farm_ip_tx)
+    }
+
+    fn add_stellar_payout_v2address_call(
+        &self,
+        cl: &Client,
+        farm_id: u32,
+        stellar_address: String,
+    ) -> Result<EncodedCall, Error> {
+        let add_payout_tx = subxt::dynamic::tx(
+            TFGRID,
+            "add_stellar_payout_v2address",
+            vec![
+                ("farm_id", Value::u128(farm_id.into())),
+                ("stellar_address", Value::from_bytes(stellar_address)),
+            ],
+        );
+
+        call(cl, &add_payout_tx)
+    }
+
+    fn set_farm_dedicated_call(
+        &self,
+        cl: &Client,
+        farm_id: u32,
+        dedicated: bool,
+    ) -> Result<EncodedCall, Error> {
+        let set_dedicated_tx = subxt::dynamic::tx(
+            TFGRID,
+            "set_farm_dedicated",
+            vec![
+                ("farm_id", Value::u128(farm_id.into())),
+                ("dedicated", Value::bool(dedicated)),
+            ],
+        );
+
+        call(cl, &set_dedicated_tx)
+    }
+
+    fn farm_event(
+        &self,
+        cl: &Client,
+        receipt: &TxReceipt,
+    ) -> Result<Option<types::TfgridFarm>, Error> {
+        let event = receipt.events.iter().find(|ev| {
+            ev.pallet_name() == TFGRID && matches!(ev.variant_name(), "FarmStored" | "FarmUpdated")
+        });
+        let fields = match event {
+            Some(event) => event.field_values()?,
+            None => return Ok(None),
+        };
+
+        let farm = fields
+            .values()
+            .next()
+            .ok_or_else(|| decode_error("empty event"))?;
+        decode_farm(&Decoder::new(&cl.api.metadata()), farm).map(Some)
+    }
+
+    fn create_node_call(
+        &self,
+        cl: &Client,
+        farm_id: u32,
+        resources: types::Resources,
+        location: types::Location,
+        interfaces: Vec<types::Interface>,
+        secure_boot: bool,
+        virtualized: bool,
+        serial_number: Option<String>,
+    ) -> Result<EncodedCall, Error> {
+        let (resources, location, interfaces) = node_input(resources, location, interfaces);
+        let create_node_tx = subxt::dynamic::tx(
+            TFGRID,
+            "create_node",
+            vec![
+                ("farm_id", Value::u128(farm_id.into())),
+                ("resources", resources),
+                ("location", location),
+                ("interfaces", interfaces),
+                ("secure_boot", Value::bool(secure_boot)),
+                ("virtualized", Value::bool(virtualized)),
+                (
+                    "serial_number",
+                    optional(serial_number.map(Value::from_bytes)),
+                ),
+            ],
+        );
+
+        call(cl, &create_node_tx)
+    }
+
+    fn update_node_call(
+        &self,
+        cl: &Client,
+        node_id: u32,
+        farm_id: u32,
+        resources: types::Resources,
+        location: types::Location,
+        interfaces: Vec<types::Interface>,
+        secure_boot: bool,
+        virtualized: bool,
+        serial_number: Option<String>,
+    ) -> Result<EncodedCall, Error> {
+        let (resources, location, interfaces) = node_input(resources, location, interfaces);
+        let update_node_tx = subxt::dynamic::tx(
+            TFGRID,
+            "update_node",
+            vec![
+                ("node_id", Value::u128(node_id.into())),
+                ("farm_id", Value::u128(farm_id.into())),
+                ("resources", resources),
+                ("location", location),
+                ("interfaces", interfaces),
+                ("secure_boot", Value::bool(secure_boot)),
+                ("virtualized", Value::bool(virtualized)),
+                (
+                    "serial_number",
+                    optional(serial_number.map(Value::from_bytes)),
+                ),
+            ],
+        );
+
+        call(cl, &update_node_tx)
+    }
+
+    fn delete_node_call(&self, cl: &Client, id: u32) -> Result<EncodedCall, Error> {
+        let delete_node_tx =
+            subxt::dynamic::tx(TFGRID, "delete_node", vec![("id", Value::u128(id.into()))]);
+
+        call(cl, &delete_node_tx)
+    }
+
+    fn add_node_public_config_call(
+        &self,
+        cl: &Client,
+        farm_id: u32,
+        node_id: u32,
+        public_config: Option<types::PublicConfig>,
+    ) -> Result<EncodedCall, Error> {
+        let ip = |ip: types::IP| {
+            Value::named_composite([
+                ("ip", Value::from_bytes(ip.ip)),
+                ("gw", Value::from_bytes(ip.gw)),
+            ])
+        };
+        let public_config = public_config.map(|config| {
+            Value::named_composite([
+                ("ip4", ip(config.ip4)),
+                ("ip6", optional(config.ip6.map(ip))),
+                ("domain", optional(config.domain.map(Value::from_bytes))),
+            ])
+        });
+        let add_public_config_tx = subxt::dynamic::tx(
+            TFGRID,
+            "add_node_public_config",
+            vec![
+                ("farm_id", Value::u128(farm_id.into())),
+                ("node_id", Value::u128(node_id.into())),
+                ("public_config", optional(public_config)),
+            ],
+        );
+
+        call(cl, &add_public_config_tx)
+    }
+
+    fn node_event(
+        &self,
+        cl: &Client,
+        receipt: &TxReceipt,
+    ) -> Result<Option<types::TfgridNode>, Error> {
+        let event = receipt.events.iter().find(|ev| {
+            ev.pallet_name() == TFGRID && matches!(ev.variant_name(), "NodeStored" | "NodeUpdated")
+        });
+        let fields = match event {
+            Some(event) => event.field_values()?,
+            None => return Ok(None),
+        };
+
+        let node = fields
+            .values()
+            .next()
+            .ok_or_else(|| decode_error("empty event"))?;
+        decode_node(&Decoder::new(&cl.api.metadata()), node).map(Some)
+    }
+
+    fn report_uptime_call(&self, cl: &Client, uptime: u64) -> Result<EncodedCall, Error> {
+        let report_uptime_tx = subxt::dynamic::tx(
+            TFGRID,
+            "report_uptime",
+            vec![("uptime", Value::u128(uptime.into()))],
+        );
+
+        call(cl, &report_uptime_tx)
+    }
+
+    fn change_power_state_call(
+        &self,
+        cl: &Client,
+        state: types::PowerTarget,
+    ) -> Result<EncodedCall, Error> {
+        require_power(cl)?;
+        let change_power_state_tx = subxt::dynamic::tx(
+            TFGRID,
+            "change_power_state",
+            vec![("power_state", power(state))],
+        );
+
+        call(cl, &change_power_state_tx)
+    }
+
+    fn change_power_target_call(
+        &self,
+        cl: &Client,
+        node_id: u32,
+        target: types::PowerTarget,
+    ) -> Result<EncodedCall, Error> {
+        require_power(cl)?;
+        let change_power_target_tx = subxt::dynamic::tx(
+            TFGRID,
+            "change_power_target",
+            vec![
+                ("node_id", Value::u128(node_id.into())),
+                ("power_target", power(target)),
+            ],
+        );
+
+        call(cl, &change_power_target_tx)
+    }
+
+    fn uptime_event(
+        &self,
+        cl: &Client,
+        receipt: &TxReceipt,
+    ) -> Result<Option<types::UptimeReport>, Error> {
+        let event = receipt
+            .events
+            .iter()
+            .find(|ev| ev.pallet_name() == TFGRID && ev.variant_name() == "NodeUptimeReported");
+        let fields = match event {
+            Some(event) => event.field_values()?,
+            None => return Ok(None),
+        };
+
+        let d = Decoder::new(&cl.api.metadata());
+        match fields.values().collect::<Vec<_>>().as_slice() {
+            [node_id, timestamp, uptime] => Ok(Some(types::UptimeReport {
+                node_id: d.uint(node_id)?,
+                timestamp: d.uint(timestamp)?,
+                uptime: d.uint(uptime)?,
+            })),
+            _ => Err(decode_error("unexpected NodeUptimeReported fields")),
+        }
+    }
+
+    fn create_entity_call(
+        &self,
+        cl: &Client,
+        target: AccountId32,
+        name: String,
+        country: String,
+        city: String,
+        signature: String,
+    ) -> Result<EncodedCall, Error> {
+        let create_entity_tx = subxt::dynamic::tx(
+            TFGRID,
+            "create_entity",
+            vec![
+                ("target", Value::from_bytes(target)),
+                ("name", Value::from_bytes(name)),
+                ("country", Value::from_bytes(country)),
+                ("city", Value::from_bytes(city)),
+                ("signature", Value::from_bytes(signature)),
+            ],
+        );
+
+        call(cl, &create_entity_tx)
+    }
+
+    fn update_entity_call(
+        &self,
+        cl: &Client,
+        name: String,
+        country: String,
+        city: String,
+    ) -> Result<EncodedCall, Error> {
+        let update_entity_tx = subxt::dynamic::tx(
+            TFGRID,
+            "update_entity",
+            vec![
+                ("name", Value::from_bytes(name)),
+                ("country", Value::from_bytes(country)),
+                ("city", Value::from_bytes(city)),
+            ],
+        );
+
+        call(cl, &update_entity_tx)
+    }
+
+    fn delete_entity_call(&self, cl: &Client) -> Result<EncodedCall, Error> {
+        let delete_entity_tx =
+            subxt::dynamic::tx(TFGRID, "delete_entity", Vec::<(&str, Value)>::new());
+
+        call(cl, &delete_entity_tx)
+    }
+
+    fn add_twin_entity_call(
+        &self,
+        cl: &Client,
+        twin_id: u32,
+        entity_id: u32,
+        signature: String,
+    ) -> Result<EncodedCall, Error> {
+        let add_twin_entity_tx = subxt::dynamic::tx(
+            TFGRID,
+            "add_twin_entity",
+            vec![
+                ("twin_id", Value::u128(twin_id.into())),
+                ("entity_id", Value::u128(entity_id.into())),
+                ("signature", Value::from_bytes(signature)),
+            ],
+        );
+
+        call(cl, &add_twin_entity_tx)
+    }
+
+    fn delete_twin_entity_call(
+        &self,
+        cl: &Client,
+        twin_id: u32,
+        entity_id: u32,
+    ) -> Result<EncodedCall, Error> {
+        let delete_twin_entity_tx = subxt::dynamic::tx(
+            TFGRID,
+            "delete_twin_entity",
+            vec![
+                ("twin_id", Value::u128(twin_id.into())),
+                ("entity_id", Value::u128(entity_id.into())),
+            ],
+        );
+
+        call(cl, &delete_twin_entity_tx)
+    }
+
+    fn entity_event(
+        &self,
+        cl: &Client,
+        receipt: &TxReceipt,
+    ) -> Result<Option<types::Entity>, Error> {
+        let event = receipt.events.iter().find(|ev| {
+            ev.pallet_name() == TFGRID
+                && matches!(ev.variant_name(), "EntityStored" | "EntityUpdated")
+        });
+        let fields = match event {
+            Some(event) => event.field_values()?,
+            None => return Ok(None),
+        };
+
+        let entity = fields
+            .values()
+            .next()
+            .ok_or_else(|| decode_error("empty event"))?;
+        decode_entity(&Decoder::new(&cl.api.metadata()), entity).map(Some)
+    }
+
+    async fn create_twin(
+        &self,
+        cl: &Client,
+        signer: &dyn TfchainSigner,
+        spec: types::TwinSpec,
+        opts: &TxOptions,
+    ) -> Result<u32, Error> {
+        let create_twin_tx = self.create_twin_call(cl, spec)?;
+
+        let receipt = cl.submit(&create_twin_tx, signer, opts).await?;
+        let twin = find_event(&receipt, TFGRID, "TwinStored")?;
+
+        let decoder = Decoder::new(&cl.api.metadata());
+        let twin = twin
+            .values()
+            .next()
+            .ok_or_else(|| decode_error("empty event"))?;
+        decoder.uint(decoder.field(twin, "id")?)
+    }
+
+    async fn update_twin(
+        &self,
+        cl: &Client,
+        signer: &dyn TfchainSigner,
+        spec: types::TwinSpec,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let update_twin_tx = self.update_twin_call(cl, spec)?;
+
+        let receipt = cl.submit(&update_twin_tx, signer, opts).await?;
+        if opts.wait_for != WaitFor::Broadcast {
+            find_event(&receipt, TFGRID, "TwinUpdated")?;
+        }
+
+        Ok(receipt)
+    }
+
+    async fn sign_terms_and_conditions(
+        &self,
+        cl: &Client,
+        signer: &dyn TfchainSigner,
+        document_link: String,
+        document_hash: String,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error> {
+        let sign_tandc_tx =
+            self.sign_terms_and_conditions_call(cl, document_link, document_hash)?;
+
+        cl.submit(&sign_tandc_tx, signer, opts).await
+    }
+
+    async fn get_twin_by_id(
+        &self,
+        cl: &Client,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::Twin>, Error> {
+        let twin = match fetch(cl, TFGRID, "Twins", Value::u128(id.into()), at_block).await? {
+            Some(twin) => twin,
+            None => return Ok(None),
+        };
+
+        decode_twin(&Decoder::new(&cl.api.metadata()), &twin).map(Some)
+    }
+
+    async fn get_twin_id_by_account(
+        &self,
+        cl: &Client,
+        account: AccountId32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<u32>, Error> {
+        let key = Value::from_bytes(account);
+        match fetch(cl, TFGRID, "TwinIdByAccountID", key, at_block).await? {
+            Some(id) => Ok(Some(Decoder::new(&cl.api.metadata()).uint(&id)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_contract_by_id(
+        &self,
+        cl: &Client,
+        id: u64,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::Contract>, Error> {
+        let key = Value::u128(id.into());
+        let contract = match fetch(cl, "SmartContractModule", "Contracts", key, at_block).await? {
+            Some(contract) => contract,
+            None => return Ok(None),
+        };
+
+        decode_contract(&Decoder::new(&cl.api.metadata()), &contract).map(Some)
+    }
+
+    async fn get_node_by_id(
+        &self,
+        cl: &Client,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::TfgridNode>, Error> {
+        let node = match fetch(cl, TFGRID, "Nodes", Value::u128(id.into()), at_block).await? {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+
+        let d = Decoder::new(&cl.api.metadata());
+        let mut node = decode_node(&d, &node)?;
+
+        // the power of nodes is only stored by newer runtimes, nodes that never
+        // changed it are up
+        if has_storage(cl, "NodePower") {
+            if let Some(power) =
+                fetch(cl, TFGRID, "NodePower", Value::u128(id.into()), at_block).await?
+            {
+                let target = match d.variant(d.field(&power, "target")?)?.0 {
+                    "Down" => types::PowerTarget::Down,
+                    _ => types::PowerTarget::Up,
+                };
+                let state = match d.variant(d.field(&power, "state")?)? {
+                    ("Down", values) => match values.as_slice() {
+                        [block] => types::PowerState::Down(d.uint(block)?),
+                        _ => return Err(decode_error("invalid power state")),
+                    },
+                    _ => types::PowerState::Up,
+                };
+                node.power.target = target;
+                node.power.state = state;
+            }
+        }
+
+        Ok(Some(node))
+    }
+
+    async fn get_farm_by_id(
+        &self,
+        cl: &Client,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::TfgridFarm>, Error> {
+        let farm = match fetch(cl, TFGRID, "Farms", Value::u128(id.into()), at_block).await? {
+            Some(farm) => farm,
+            None => return Ok(None),
+        };
+
+        decode_farm(&Decoder::new(&cl.api.metadata()), &farm).map(Some)
+    }
+
+    async fn get_entity_by_id(
+        &self,
+        cl: &Client,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::Entity>, Error> {
+        let entity = match fetch(cl, TFGRID, "Entities", Value::u128(id.into()), at_block).await? {
+            Some(entity) => entity,
+            None => return Ok(None),
+        };
+
+        decode_entity(&Decoder::new(&cl.api.metadata()), &entity).map(Some)
+    }
+
+    // entity IDs start at 1, a name without entity is stored as 0
+    async fn get_entity_id_by_name(
+        &self,
+        cl: &Client,
+        name: String,
+        at_block: Option<Hash>,
+    ) -> Result<Option<u32>, Error> {
+        let key = Value::from_bytes(name);
+        match fetch(cl, TFGRID, "EntityIdByName", key, at_block).await? {
+            Some(id) => Ok(Some(Decoder::new(&cl.api.metadata()).uint(&id)?).filter(|id| *id != 0)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_balance(
+        &self,
+        cl: &Client,
+        account: &AccountId32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::SystemAccountInfo>, Error> {
+        let key = Value::from_bytes(account);
+        let info = match fetch(cl, "System", "Account", key, at_block).await? {
+            Some(info) => info,
+            None => return Ok(None),
+        };
+
+        decode_account_info(&Decoder::new(&cl.api.metadata()), &info).map(Some)
+    }
+}
+
+async fn fetch(
+    cl: &Client,
+    pallet: &str,
+    entry: &str,
+    key: Value,
+    at_block: Option<Hash>,
+) -> Result<Option<DecodedValue>, Error> {
+    let address = subxt::dynamic::storage(pallet, entry, vec![key]);
+
+    match cl.api.storage().fetch(&address, at_block).await? {
+        Some(thunk) => Ok(Some(thunk.to_value()?)),
+        None => Ok(None),
+    }
+}
+
+// decodes a twin as stored on chain
+fn decode_twin(d: &Decoder, twin: &DecodedValue) -> Result<types::Twin, Error> {
+    // older runtimes store an `ip` instead of the relay and public key
+    let (relay, pk, ip) = match d.optional_field(twin, "relay") {
+        Some(relay) => (
+            d.option(relay)?.map(|v| d.string(v)).transpose()?,
+            d.field(twin, "pk")
+                .and_then(|pk| d.option(pk))?
+                .map(|v| d.bytes(v))
+                .transpose()?,
+            None,
+        ),
+        None => (None, None, Some(d.string(d.field(twin, "ip")?)?)),
+    };
+
+    let entities = d
+        .seq(d.field(twin, "entities")?)?
+        .into_iter()
+        .map(|e| {
+            Ok(types::EntityProof {
+                entity_id: d.uint(d.field(e, "entity_id")?)?,
+                signature: d.string(d.field(e, "signature")?)?,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(types::Twin {
+        id: d.uint(d.field(twin, "id")?)?,
+        account: d.account(d.field(twin, "account_id")?)?,
+        relay,
+        entities,
+        pk,
+        ip,
+    })
+}
+
+// decodes a contract as stored on chain
+fn decode_contract(d: &Decoder, contract: &DecodedValue) -> Result<types::Contract, Error> {
+    let (state, values) = d.variant(d.field(contract, "state")?)?;
+    let state = match (state, values.as_slice()) {
+        ("Created", _) => types::ContractState::Created,
+        ("Deleted", [cause]) => match d.variant(cause)?.0 {
+            "CanceledByUser" => types::ContractState::Deleted(types::Cause::CanceledByUser),
+            "OutOfFunds" => types::ContractState::Deleted(types::Cause::OutOfFunds),
+            name => return Err(decode_error(format!("unknown contract cause {}", name))),
+        },
+        // the block is stored as a u64, it fails to decode if it does not fit
+        ("GracePeriod", [block]) => types::ContractState::GracePeriod(d.uint(block)?),
+        (name, _) => return Err(decode_error(format!("unknown contract state {}", name))),
+    };
+
+    let (contract_type, values) = d.variant(d.field(contract, "contract_type")?)?;
+    let contract_type = match (contract_type, values.as_slice()) {
+        ("NodeContract", [nc]) => {
+            let public_ips_list = d
+                .seq(d.field(nc, "public_ips_list")?)?
+                .into_iter()
+                .map(|ip| {
+                    Ok(types::IP {
+                        ip: d.string(d.field(ip, "ip")?)?,
+                        gw: d.string(d.field(ip, "gateway")?)?,
+                    })
+                })
+                .collect::<Result<_, Error>>()?;
+
+            types::ContractData::NodeContract(types::NodeContract {
+                node_id: d.uint(d.field(nc, "node_id")?)?,
+                deployment_hash: d
+                    .bytes(d.field(nc, "deployment_hash")?)?
+                    .try_into()
+                    .map_err(|_| decode_error("invalid deployment hash"))?,
+                deployment_data: d.string(d.field(nc, "deployment_data")?)?,
+                public_ips: d.uint(d.field(nc, "public_ips")?)?,
+                public_ips_list,
+            })
+        }
+        ("NameContract", [nmc]) => types::ContractData::NameContract(types::NameContract {
+            name: d.string(d.field(nmc, "name")?)?,
+        }),
+        ("RentContract", [rc]) => types::ContractData::RentContract(types::RentContract {
+            node_id: d.uint(d.field(rc, "node_id")?)?,
+        }),
+        (name, _) => return Err(decode_error(format!("unknown contract type {}", name))),
+    };
+
+    Ok(types::Contract {
+        version: d.uint(d.field(contract, "version")?)?,
+        state,
+        contract_id: d.uint(d.field(contract, "contract_id")?)?,
+        twin_id: d.uint(d.field(contract, "twin_id")?)?,
+        contract_type,
+        solution_provider_id: d
+            .option(d.field(contract, "solution_provider_id")?)?
+            .map(|v| d.uint(v))
+            .transpose()?,
+    })
+}
+
+// decodes the account info of the system pallet
+fn decode_account_info(
+    d: &Decoder,
+    info: &DecodedValue,
+) -> Result<types::SystemAccountInfo, Error> {
+    let data = d.field(info, "data")?;
+    Ok(types::SystemAccountInfo {
+        nonce: d.uint(d.field(info, "nonce")?)?,
+        consumers: d.uint(d.field(info, "consumers")?)?,
+        providers: d.uint(d.field(info, "providers")?)?,
+        sufficients: d.uint(d.field(info, "sufficients")?)?,
+        data: pallet_balances::AccountData {
+            free: d.uint(d.field(data, "free")?)?,
+            reserved: d.uint(d.field(data, "reserved")?)?,
+            misc_frozen: d.uint(d.field(data, "misc_frozen")?)?,
+            fee_frozen: d.uint(d.field(data, "fee_frozen")?)?,
+        },
+    })
+}
+
+// The raw storage entries proven by the `*_with_proof` getters of the client are
+// decoded like the getters above do, with the metadata only. This does not depend on
+// the runtime the client was generated for and needs no node, so a proof can be
+// checked offline. No entry decodes to `None`.
+pub(crate) fn twin_from_raw(
+    metadata: &Metadata,
+    raw: Option<&[u8]>,
+) -> Result<Option<types::Twin>, Error> {
+    decode_raw(metadata, TFGRID, "Twins", raw, decode_twin)
+}
+
+pub(crate) fn twin_id_from_raw(
+    metadata: &Metadata,
+    raw: Option<&[u8]>,
+) -> Result<Option<u32>, Error> {
+    decode_raw(metadata, TFGRID, "TwinIdByAccountID", raw, |d, id| {
+        d.uint(id)
+    })
+}
+
+pub(crate) fn farm_from_raw(
+    metadata: &Metadata,
+    raw: Option<&[u8]>,
+) -> Result<Option<types::TfgridFarm>, Error> {
+    decode_raw(metadata, TFGRID, "Farms", raw, decode_farm)
+}
+
+// the power of the node is stored in its own entry which is not part of the proof,
+// the node is decoded as up
+pub(crate) fn node_from_raw(
+    metadata: &Metadata,
+    raw: Option<&[u8]>,
+) -> Result<Option<types::TfgridNode>, Error> {
+    decode_raw(metadata, TFGRID, "Nodes", raw, decode_node)
+}
+
+pub(crate) fn contract_from_raw(
+    metadata: &Metadata,
+    raw: Option<&[u8]>,
+) -> Result<Option<types::Contract>, Error> {
+    decode_raw(
+        metadata,
+        "SmartContractModule",
+        "Contracts",
+        raw,
+        decode_contract,
+    )
+}
+
+pub(crate) fn account_info_from_raw(
+    metadata: &Metadata,
+    raw: Option<&[u8]>,
+) -> Result<Option<types::SystemAccountInfo>, Error> {
+    decode_raw(metadata, "System", "Account", raw, decode_account_info)
+}
+
+// the whole entry has to be decoded, trailing bytes mean it is not of the expected type
+fn decode_raw<T>(
+    metadata: &Metadata,
+    pallet: &str,
+    entry: &str,
+    raw: Option<&[u8]>,
+    decode: fn(&Decoder, &DecodedValue) -> Result<T, Error>,
+) -> Result<Option<T>, Error> {
+    let mut raw = match raw {
+        Some(raw) => raw,
+        None => return Ok(None),
+    };
+
+    let value = DecodedValue::decode_storage_with_metadata(&mut raw, pallet, entry, metadata)?;
+    if !raw.is_empty() {
+        return Err(decode_error(format!(
+            "{} bytes left after decoding {}::{}",
+            raw.len(),
+            pallet,
+            entry
+        )));
+    }
+
+    decode(&Decoder::new(metadata), &value).map(Some)
+}
+
+// decodes a farm as stored on chain or emitted in the farm events
+fn decode_farm(d: &Decoder, farm: &DecodedValue) -> Result<types::TfgridFarm, Error> {
+    let public_ips = d
+        .seq(d.field(farm, "public_ips")?)?
+        .into_iter()
+        .map(|ip| {
+            Ok(types::FarmPublicIP {
+                ip: d.string(d.field(ip, "ip")?)?,
+                gateway: d.string(d.field(ip, "gateway")?)?,
+                contract_id: d.uint(d.field(ip, "contract_id")?)?,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+    let opt_uint = |v: &DecodedValue, name: &str| -> Result<Option<u64>, Error> {
+        d.option(d.field(v, name)?)?.map(|v| d.uint(v)).transpose()
+    };
+
+    let farming_policy_limits = match d.option(d.field(farm, "farming_policy_limits")?)? {
+        Some(lim) => Some(types::FarmingPolicyLimit {
+            farming_policy_id: d.uint(d.field(lim, "farming_policy_id")?)?,
+            cu: opt_uint(lim, "cu")?,
+            su: opt_uint(lim, "su")?,
+            end: opt_uint(lim, "end")?,
+            node_count: d
+                .option(d.field(lim, "node_count")?)?
+                .map(|v| d.uint(v))
+                .transpose()?,
+            node_certification: d.boolean(d.field(lim, "node_certification")?)?,
+        }),
+        None => None,
+    };
+
+    let certification = match d.variant(d.field(farm, "certification")?)?.0 {
+        "NotCertified" => types::FarmCertification::NotCertified,
+        "Gold" => types::FarmCertification::Gold,
+        name => return Err(decode_error(format!("unknown farm certification {}", name))),
+    };
+
+    Ok(types::TfgridFarm {
+        version: d.uint(d.field(farm, "version")?)?,
+        id: d.uint(d.field(farm, "id")?)?,
+        name: d.string(d.field(farm, "name")?)?,
+        twin_id: d.uint(d.field(farm, "twin_id")?)?,
+        pricing_policy_id: d.uint(d.field(farm, "pricing_policy_id")?)?,
+        certification,
+        dedicated_farm: d.boolean(d.field(farm, "dedicated_farm")?)?,
+        farming_policy_limits,
+        public_ips,
+    })
+}
+
+// decodes a node as stored on chain or emitted in the node events
+fn decode_node(d: &Decoder, node: &DecodedValue) -> Result<types::TfgridNode, Error> {
+    let res = d.field(node, "resources")?;
+    let mut resources = types::ConsumableResources::default();
+    resources.total_resources = types::Resources {
+        hru: d.uint(d.field(res, "hru")?)?,
+        sru: d.uint(d.field(res, "sru")?)?,
+        cru: d.uint(d.field(res, "cru")?)?,
+        mru: d.uint(d.field(res, "mru")?)?,
+    };
+
+    let loc = d.field(node, "location")?;
+    let location = types::Location {
+        city: d.string(d.field(loc, "city")?)?,
+        country: d.string(d.field(loc, "country")?)?,
+        latitude: d.string(d.field(loc, "latitude")?)?,
+        longitude: d.string(d.field(loc, "longitude")?)?,
+    };
+
+    let ip = |v: &DecodedValue| -> Result<types::IP, Error> {
+        Ok(types::IP {
+            ip: d.string(d.field(v, "ip")?)?,
+            gw: d.string(d.field(v, "gw")?)?,
+        })
+    };
+
+    let public_config = match d.option(d.field(node, "public_config")?)? {
+        Some(config) => Some(types::PublicConfig {
+            ip4: ip(d.field(config, "ip4")?)?,
+            ip6: d.option(d.field(config, "ip6")?)?.map(ip).transpose()?,
+            domain: d
+                .option(d.field(config, "domain")?)?
+                .map(|v| d.string(v))
+                .transpose()?,
+        }),
+        None => None,
+    };
+
+    let interfaces = d
+        .seq(d.field(node, "interfaces")?)?
+        .into_iter()
+        .map(|intf| {
+            Ok(types::Interface {
+                name: d.string(d.field(intf, "name")?)?,
+                mac: d.string(d.field(intf, "mac")?)?,
+                ips: d
+                    .seq(d.field(intf, "ips")?)?
+                    .into_iter()
+                    .map(|ip| d.string(ip))
+                    .collect::<Result<_, Error>>()?,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+    let certification = match d.variant(d.field(node, "certification")?)?.0 {
+        "Diy" => types::NodeCertification::Diy,
+        "Certified" => types::NodeCertification::Certified,
+        name => return Err(decode_error(format!("unknown node certification {}", name))),
+    };
+
+    Ok(types::TfgridNode {
+        version: d.uint(d.field(node, "version")?)?,
+        id: d.uint(d.field(node, "id")?)?,
+        farm_id: d.uint(d.field(node, "farm_id")?)?,
+        twin_id: d.uint(d.field(node, "twin_id")?)?,
+        resources,
+        location,
+        power: types::Power {
+            target: types::PowerTarget::Up,
+            state: types::PowerState::Up,
+            last_uptime: 0,
+        },
+        public_config,
+        created: d.uint(d.field(node, "created")?)?,
+        farming_policy_id: d.uint(d.field(node, "farming_policy_id")?)?,
+        interfaces,
+        certification,
+        secure_boot: d.boolean(d.field(node, "secure_boot")?)?,
+        serial_number: d
+            .option(d.field(node, "serial_number")?)?
+            .map(|v| d.string(v))
+            .transpose()?,
+        connection_price: d.uint(d.field(node, "connection_price")?)?,
+    })
+}
+
+fn decode_entity(d: &Decoder, entity: &DecodedValue) -> Result<types::Entity, Error> {
+    Ok(types::Entity {
+        version: d.uint(d.field(entity, "version")?)?,
+        id: d.uint(d.field(entity, "id")?)?,
+        name: d.string(d.field(entity, "name")?)?,
+        account: d.account(d.field(entity, "account_id")?)?,
+        country: d.string(d.field(entity, "country")?)?,
+        city: d.string(d.field(entity, "city")?)?,
+    })
+}
+
+// the node fields as taken by `create_node` and `update_node`, lists are wrapped in
+// their `BoundedVec` so a single element is not taken for the content of the wrapper
+fn node_input(
+    resources: types::Resources,
+    location: types::Location,
+    interfaces: Vec<types::Interface>,
+) -> (Value, Value, Value) {
+    let resources = Value::named_composite([
+        ("hru", Value::u128(resources.hru.into())),
+        ("sru", Value::u128(resources.sru.into())),
+        ("cru", Value::u128(resources.cru.into())),
+        ("mru", Value::u128(resources.mru.into())),
+    ]);
+    let location = Value::named_composite([
+        ("city", Value::from_bytes(location.city)),
+        ("country", Value::from_bytes(location.country)),
+        ("latitude", Value::from_bytes(location.latitude)),
+        ("longitude", Value::from_bytes(location.longitude)),
+    ]);
+    let interfaces = interfaces.into_iter().map(|intf| {
+        let ips = intf.ips.into_iter().map(Value::from_bytes);
+        Value::named_composite([
+            ("name", Value::from_bytes(intf.name)),
+            ("mac", Value::from_bytes(intf.mac)),
+            (
+                "ips",
+                Value::unnamed_composite([Value::unnamed_composite(ips)]),
+            ),
+        ])
+    });
+
+    (
+        resources,
+        location,
+        Value::unnamed_composite([Value::unnamed_composite(interfaces)]),
+    )
+}
+
+fn power(target: types::PowerTarget) -> Value {
+    match target {
+        types::PowerTarget::Up => Value::unnamed_variant("Up", []),
+        types::PowerTarget::Down => Value::unnamed_variant("Down", []),
+    }
+}
+
+// node power management is only in newer runtimes
+fn require_power(cl: &Client) -> Result<(), Error> {
+    match call_fields(&cl.api.metadata(), TFGRID, "change_power_target") {
+        Ok(_) => Ok(()),
+        Err(_) => Err(Error::FeatureUnsupportedOnRuntime {
+            runtime: "dynamic",
+            feature: "node power management",
+        }),
+    }
+}
+
+fn has_storage(cl: &Client, entry: &str) -> bool {
+    cl.api
+        .metadata()
+        .pallet(TFGRID)
+        .and_then(|pallet| pallet.storage(entry))
+        .is_ok()
+}
+
+fn optional(value: Option<Value>) -> Value {
+    match value {
+        Some(value) => Value::unnamed_variant("Some", [value]),
+        None => Value::unnamed_variant("None", []),
+    }
+}
+
+// encodes a dynamic call, checking its fields against the node metadata
+fn call<Call: TxPayload>(cl: &Client, tx: &Call) -> Result<EncodedCall, Error> {
+    Ok(EncodedCall::new(cl.api.tx().call_data(tx)?))
+}
+
+// returns the fields of the first event emitted by the extrinsic with the given name
+fn find_event(
+    receipt: &TxReceipt,
+    pallet: &'static str,
+    event: &'static str,
+) -> Result<Composite<TypeId>, Error> {
+    for ev in &receipt.events {
+        if ev.pallet_name() == pallet && ev.variant_name() == event {
+            return Ok(ev.field_values()?);
+        }
+    }
+
+    Err(Error::EventNotFound { pallet, event })
+}
+
+// builds the twin call arguments for the shape of the call in the node metadata,
+// older runtimes take an `ip` while newer ones take a `relay` and a `pk`. A field
+// of the spec the call does not take is not supported by the runtime.
+fn twin_fields(
+    metadata: &Metadata,
+    call: &str,
+    mut spec: types::TwinSpec,
+) -> Result<Vec<(String, Value)>, Error> {
+    let names = call_fields(metadata, TFGRID, call)?;
+
+    let unsupported = [
+        ("relay", spec.relay.is_some(), "twin relay"),
+        ("pk", spec.pk.is_some(), "twin public key"),
+        ("ip", spec.ip.is_some(), "twin ip"),
+    ]
+    .into_iter()
+    .find(|(name, set, _)| *set && !names.iter().any(|n| n == name));
+    if let Some((_, _, feature)) = unsupported {
+        return Err(Error::FeatureUnsupportedOnRuntime {
+            runtime: "dynamic",
+            feature,
+        });
+    }
+
+    names
+        .into_iter()
+        .map(|name| {
+            let value = match name.as_str() {
+                "relay" => optional(spec.relay.take().map(Value::from_bytes)),
+                "pk" => optional(spec.pk.take().map(Value::from_bytes)),
+                "ip" => match spec.ip.take() {
+                    Some(ip) => Value::from_bytes(ip),
+                    None => {
+                        return Err(Error::MissingField {
+                            runtime: "dynamic",
+                            field: "twin ip",
+                        })
+                    }
+                },
+                _ => {
+                    return Err(Error::Subxt(subxt::Error::Other(format!(
+                        "unsupported argument {} for {}::{}",
+                        name, TFGRID, call
+                    ))))
+                }
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
+fn call_fields(metadata: &Metadata, pallet: &str, call: &str) -> Result<Vec<String>, Error> {
+    let pallet = metadata.pallet(pallet).map_err(subxt::Error::from)?;
+    let calls = pallet
+        .call_ty_id()
+        .and_then(|id| metadata.resolve_type(id))
+        .map(|ty| ty.type_def());
+
+    match calls {
+        Some(TypeDef::Variant(variant)) => variant
+            .variants()
+            .iter()
+            .find(|v| v.name() == call)
+            .map(|v| {
+                v.fields()
+                    .iter()
+                    .filter_map(|f| f.name().cloned())
+                    .collect()
+            })
+            .ok_or_else(|| {
+                Error::Subxt(subxt::Error::Other(format!(
+                    "call {}::{} not found",
+                    pallet.name(),
+                    call
+                )))
+            }),
+        _ => Ok(vec![]),
+    }
+}
+
+fn decode_error<S: Into<String>>(msg: S) -> Error {
+    Error::Decode(subxt::Error::Other(msg.into()))
+}
+
+// Decoder reads values decoded against the node metadata by field name. Newtype
+// wrappers such as `BoundedVec` or `FarmName` are skipped so the values can be
+// read the same way regardless of how the runtime wraps them.
+struct Decoder {
+    sequences: HashSet<TypeId>,
+}
+
+impl Decoder {
+    fn new(metadata: &Metadata) -> Self {
+        let sequences = metadata
+            .types()
+            .types()
+            .iter()
+            .filter(|ty| matches!(ty.ty().type_def(), TypeDef::Sequence(_) | TypeDef::Array(_)))
+            .map(|ty| TypeId::from(ty.id()))
+            .collect();
+
+        Decoder { sequences }
+    }
+
+    // skips composites with a single unnamed field, unless the composite is a
+    // sequence with a single element
+    fn unwrap<'a>(&self, mut value: &'a DecodedValue) -> &'a DecodedValue {
+        while let ValueDef::Composite(Composite::Unnamed(values)) = &value.value {
+            if values.len() != 1 || self.sequences.contains(&value.context) {
+                break;
+            }
+            value = &values[0];
+        }
+        value
+    }
+
+    fn optional_field<'a>(&self, value: &'a DecodedValue, name: &str) -> Option<&'a DecodedValue> {
+        match &self.unwrap(value).value {
+            ValueDef::Composite(Composite::Named(fields)) => {
+                fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+            }
+            _ => None,
+        }
+    }
+
+    fn field<'a>(&self, value: &'a DecodedValue, name: &str) -> Result<&'a DecodedValue, Error> {
+        self.optional_field(value, name)
+            .ok_or_else(|| decode_error(format!("missing field {}", name)))
+    }
+
+    fn seq<'a>(&self, value: &'a DecodedValue) -> Result<Vec<&'a DecodedValue>, Error> {
+        match &self.unwrap(value).value {
+            ValueDef::Composite(Composite::Unnamed(values)) => Ok(values.iter().collect()),
+            _ => Err(decode_error("expected a sequence")),
+        }
+    }
+
+    fn bytes(&self, value: &DecodedValue) -> Result<Vec<u8>, Error> {
+        self.seq(value)?
+            .into_iter()
+            .map(|v| match &v.value {
+                ValueDef::Primitive(Primitive::U128(b)) => {
+                    u8::try_from(*b).map_err(|_| decode_error("expected a byte"))
+                }
+                _ => Err(decode_error("expected a byte")),
+            })
+            .collect()
+    }
+
+    fn string(&self, value: &DecodedValue) -> Result<String, Error> {
+        String::from_utf8(self.bytes(value)?).map_err(|_| decode_error("invalid utf8"))
+    }
+
+    fn account(&self, value: &DecodedValue) -> Result<AccountId32, Error> {
+        let bytes: [u8; 32] = self
+            .bytes(value)?
+            .try_into()
+            .map_err(|_| decode_error("invalid account id"))?;
+        Ok(AccountId32::from(bytes))
+    }
+
+    fn uint<T: TryFrom<u128>>(&self, value: &DecodedValue) -> Result<T, Error> {
+        self.unwrap(value)
+            .as_u128()
+            .and_then(|v| T::try_from(v).ok())
+            .ok_or_else(|| decode_error("expected an unsigned integer"))
+    }
+
+    fn boolean(&self, value: &DecodedValue) -> Result<bool, Error> {
+        self.unwrap(value)
+            .as_bool()
+            .ok_or_else(|| decode_error("expected a boolean"))
+    }
+
+    // returns the name of a variant and its fields
+    fn variant<'a>(
+        &self,
+        value: &'a DecodedValue,
+    ) -> Result<(&'a str, Vec<&'a DecodedValue>), Error> {
+        match &self.unwrap(value).value {
+            ValueDef::Variant(variant) => Ok((&variant.name, variant.values.values().collect())),
+            _ => Err(decode_error("expected a variant")),
+        }
+    }
+
+    fn option<'a>(&self, value: &'a DecodedValue) -> Result<Option<&'a DecodedValue>, Error> {
+        match self.variant(value)? {
+            ("None", _) => Ok(None),
+            ("Some", values) => values
+                .first()
+                .copied()
+                .map(Some)
+                .ok_or_else(|| decode_error("empty option")),
+            _ => Err(decode_error("expected an option")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtimes::devnet::{
+        self,
+        devnet::runtime_types::{self, sp_core::bounded::bounded_vec::BoundedVec},
+    };
+    use crate::runtimes::testing;
+    use subxt::ext::codec::Encode;
+
+    #[test]
+    fn twin_fields_follow_the_call_shape() {
+        let mainnet = testing::metadata("mainnet");
+        for call in ["create_twin", "update_twin"] {
+            let err = twin_fields(&mainnet, call, types::TwinSpec::default()).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    Error::MissingField {
+                        field: "twin ip",
+                        ..
+                    }
+                ),
+                "{}",
+                err
+            );
+
+            let fields =
+                twin_fields(&mainnet, call, types::TwinSpec::with_ip("::1".into())).unwrap();
+            assert_eq!(fields.len(), 1);
+            assert_eq!(fields[0].0, "ip");
+
+            let relay = types::TwinSpec::with_relay(Some("relay".into()), None);
+            assert!(matches!(
+                twin_fields(&mainnet, call, relay),
+                Err(Error::FeatureUnsupportedOnRuntime { .. })
+            ));
+        }
+
+        // newer runtimes take no ip, and a twin without relay is fine
+        let devnet = testing::metadata("devnet");
+        let fields = twin_fields(&devnet, "create_twin", types::TwinSpec::default()).unwrap();
+        let names: Vec<_> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["relay", "pk"]);
+        assert!(matches!(
+            twin_fields(
+                &devnet,
+                "create_twin",
+                types::TwinSpec::with_ip("::1".into())
+            ),
+            Err(Error::FeatureUnsupportedOnRuntime { .. })
+        ));
+    }
+
+    #[test]
+    fn decodes_twins() {
+        let twin = devnet::Twin {
+            id: 1,
+            account_id: AccountId32::new([1; 32]),
+            relay: Some(BoundedVec(b"relay.grid.tf".to_vec())),
+            entities: vec![runtime_types::pallet_tfgrid::types::EntityProof {
+                entity_id: 2,
+                signature: b"sig".to_vec(),
+            }],
+            pk: None,
+        };
+
+        let decoded = twin_from_raw(&testing::metadata("devnet"), Some(&twin.encode())).unwrap();
+        assert_eq!(
+            decoded,
+            Some(types::Twin {
+                id: 1,
+                account: AccountId32::new([1; 32]),
+                relay: Some("relay.grid.tf".into()),
+                entities: vec![types::EntityProof {
+                    entity_id: 2,
+                    signature: "sig".into(),
+                }],
+                pk: None,
+                ip: None,
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_farms() {
+        use runtime_types::tfchain_support::types::{FarmCertification, FarmingPolicyLimit};
+
+        let farm = devnet::Farm {
+            version: 4,
+            id: 1,
+            name: runtime_types::pallet_tfgrid::farm::FarmName(BoundedVec(b"farm".to_vec())),
+            twin_id: 2,
+            pricing_policy_id: 1,
+            certification: FarmCertification::Gold,
+            public_ips: BoundedVec(vec![devnet::PublicIpData {
+                ip: BoundedVec(b"185.206.122.33/24".to_vec()),
+                gateway: BoundedVec(b"185.206.122.1".to_vec()),
+                contract_id: 3,
+            }]),
+            dedicated_farm: true,
+            farming_policy_limits: Some(FarmingPolicyLimit {
+                farming_policy_id: 1,
+                cu: Some(10),
+                su: None,
+                end: Some(100),
+                node_count: Some(5),
+                node_certification: false,
+            }),
+        };
+
+        let decoded = farm_from_raw(&testing::metadata("devnet"), Some(&farm.encode())).unwrap();
+        assert_eq!(
+            decoded,
+            Some(types::TfgridFarm {
+                version: 4,
+                id: 1,
+                name: "farm".into(),
+                twin_id: 2,
+                pricing_policy_id: 1,
+                certification: types::FarmCertification::Gold,
+                dedicated_farm: true,
+                farming_policy_limits: Some(types::FarmingPolicyLimit {
+                    farming_policy_id: 1,
+                    cu: Some(10),
+                    su: None,
+                    end: Some(100),
+                    node_count: Some(5),
+                    node_certification: false,
+                }),
+                public_ips: vec![types::FarmPublicIP {
+                    ip: "185.206.122.33/24".into(),
+                    gateway: "185.206.122.1".into(),
+                    contract_id: 3,
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_nodes() {
+        use runtime_types::pallet_tfgrid::{interface, node};
+        use runtime_types::tfchain_support::{resources::Resources, types::NodeCertification};
+
+        let bytes = |v: &str| BoundedVec(v.as_bytes().to_vec());
+        let node = devnet::Node {
+            version: 6,
+            id: 7,
+            farm_id: 1,
+            twin_id: 8,
+            resources: Resources {
+                hru: 1,
+                sru: 2,
+                cru: 3,
+                mru: 4,
+            },
+            location: node::Location {
+                city: node::CityName(bytes("Cairo")),
+                country: node::CountryName(bytes("Egypt")),
+                latitude: bytes("30.0"),
+                longitude: bytes("31.2"),
+            },
+            public_config: Some(devnet::PublicConfig {
+                ip4: devnet::IP4 {
+                    ip: bytes("185.206.122.33/24"),
+                    gw: bytes("185.206.122.1"),
+                },
+                ip6: None,
+                domain: Some(bytes("node.grid.tf")),
+            }),
+            created: 1000,
+            farming_policy_id: 1,
+            interfaces: vec![devnet::Interface {
+                name: interface::InterfaceName(bytes("zos")),
+                mac: interface::InterfaceMac(bytes("00:11:22:33:44:55")),
+                ips: BoundedVec(vec![interface::InterfaceIp(bytes("10.0.0.1"))]),
+            }],
+            certification: NodeCertification::Certified,
+            secure_boot: false,
+            virtualized: false,
+            serial_number: None,
+            connection_price: 80,
+        };
+
+        let decoded = node_from_raw(&testing::metadata("devnet"), Some(&node.encode()))
+            .unwrap()
+            .unwrap();
+        assert_eq!((decoded.id, decoded.farm_id, decoded.twin_id), (7, 1, 8));
+        assert_eq!(
+            decoded.resources.total_resources,
+            types::Resources {
+                hru: 1,
+                sru: 2,
+                cru: 3,
+                mru: 4,
+            }
+        );
+        assert_eq!(
+            decoded.location,
+            types::Location {
+                city: "Cairo".into(),
+                country: "Egypt".into(),
+                latitude: "30.0".into(),
+                longitude: "31.2".into(),
+            }
+        );
+        assert_eq!(
+            decoded.public_config,
+            Some(types::PublicConfig {
+                ip4: types::IP {
+                    ip: "185.206.122.33/24".into(),
+                    gw: "185.206.122.1".into(),
+                },
+                ip6: None,
+                domain: Some("node.grid.tf".into()),
+            })
+        );
+        assert_eq!(
+            decoded.interfaces,
+            vec![types::Interface {
+                name: "zos".into(),
+                mac: "00:11:22:33:44:55".into(),
+                ips: vec!["10.0.0.1".into()],
+            }]
+        );
+        assert_eq!(decoded.certification, types::NodeCertification::Certified);
+    }
+
+    #[test]
+    fn decodes_contracts() {
+        use runtime_types::pallet_smart_contract::types::{
+            Cause, Contract, ContractData, ContractState, NameContract,
+        };
+
+        let metadata = testing::metadata("devnet");
+        let contract = |state| Contract {
+            version: 4,
+            state,
+            contract_id: 9,
+            twin_id: 2,
+            contract_type: ContractData::NameContract(NameContract {
+                name: runtime_types::pallet_smart_contract::name_contract::NameContractName(
+                    BoundedVec(b"name".to_vec()),
+                ),
+            }),
+            solution_provider_id: Some(1),
+        };
+        let decode = |state| contract_from_raw(&metadata, Some(&contract(state).encode()));
+
+        assert_eq!(
+            decode(ContractState::Deleted(Cause::OutOfFunds)).unwrap(),
+            Some(types::Contract {
+                version: 4,
+                state: types::ContractState::Deleted(types::Cause::OutOfFunds),
+                contract_id: 9,
+                twin_id: 2,
+                contract_type: types::ContractData::NameContract(types::NameContract {
+                    name: "name".into(),
+                }),
+                solution_provider_id: Some(1),
+            })
+        );
+
+        let state = |state| decode(state).unwrap().unwrap().state;
+        assert_eq!(
+            state(ContractState::Deleted(Cause::CanceledByUser)),
+            types::ContractState::Deleted(types::Cause::CanceledByUser)
+        );
+        assert_eq!(
+            state(ContractState::GracePeriod(1000)),
+            types::ContractState::GracePeriod(1000)
+        );
+        // a block that does not fit is not truncated
+        assert!(decode(ContractState::GracePeriod(u64::from(u32::MAX) + 1)).is_err());
+    }
+}
@@ -1,7 +1,14 @@
 #![allow(clippy::all)]
 
-pub mod devnet;
-pub mod local;
-pub mod mainnet;
-pub mod testnet;
+#[macro_use]
 pub mod types;
+#[macro_use]
+mod runtime;
+
+pub use runtime::TfchainRuntime;
+pub mod dynamic;
+#[cfg(test)]
+pub(crate) mod testing;
+
+// one module per network, generated by the build script from `artifacts/*.scale`
+include!(concat!(env!("OUT_DIR"), "/runtimes.rs"));
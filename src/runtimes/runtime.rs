@@ -0,0 +1,1219 @@
+use super::types::{self, Hash};
+use super::Runtime;
+use crate::client::Client;
+use crate::error::Error;
+use crate::signer::TfchainSigner;
+use crate::tx::{EncodedCall, TxOptions, TxReceipt};
+use std::str::FromStr;
+use subxt::{ext::sp_runtime::AccountId32, OnlineClient, PolkadotConfig};
+
+// TfchainRuntime is implemented once per generated runtime module, the client
+// forwards all chain specific calls to the implementation of its runtime.
+#[async_trait::async_trait]
+pub trait TfchainRuntime: Send + Sync {
+    // name of the runtime as accepted by `Runtime::from_str`
+    fn name(&self) -> &'static str;
+
+    // the metadata the runtime code was generated from, if any
+    fn metadata(&self) -> Option<&'static [u8]>;
+
+    // checks that the metadata of the node matches the generated code
+    fn validate(&self, api: &OnlineClient<PolkadotConfig>) -> bool;
+
+    // the calls submitted by the methods below, to be estimated or dry run
+    fn create_twin_call(&self, cl: &Client, spec: types::TwinSpec) -> Result<EncodedCall, Error>;
+
+    fn update_twin_call(&self, cl: &Client, spec: types::TwinSpec) -> Result<EncodedCall, Error>;
+
+    fn sign_terms_and_conditions_call(
+        &self,
+        cl: &Client,
+        document_link: String,
+        document_hash: String,
+    ) -> Result<EncodedCall, Error>;
+
+    fn create_farm_call(
+        &self,
+        cl: &Client,
+        name: String,
+        public_ips: Vec<types::FarmPublicIPInput>,
+    ) -> Result<EncodedCall, Error>;
+
+    fn update_farm_call(&self, cl: &Client, id: u32, name: String) -> Result<EncodedCall, Error>;
+
+    fn add_farm_ip_call(
+        &self,
+        cl: &Client,
+        id: u32,
+        ip: String,
+        gateway: String,
+    ) -> Result<EncodedCall, Error>;
+
+    fn remove_farm_ip_call(&self, cl: &Client, id: u32, ip: String) -> Result<EncodedCall, Error>;
+
+    fn add_stellar_payout_v2address_call(
+        &self,
+        cl: &Client,
+        farm_id: u32,
+        stellar_address: String,
+    ) -> Result<EncodedCall, Error>;
+
+    fn set_farm_dedicated_call(
+        &self,
+        cl: &Client,
+        farm_id: u32,
+        dedicated: bool,
+    ) -> Result<EncodedCall, Error>;
+
+    // the farm of the first `FarmStored` or `FarmUpdated` event emitted by the extrinsic
+    fn farm_event(
+        &self,
+        cl: &Client,
+        receipt: &TxReceipt,
+    ) -> Result<Option<types::TfgridFarm>, Error>;
+
+    fn create_node_call(
+        &self,
+        cl: &Client,
+        farm_id: u32,
+        resources: types::Resources,
+        location: types::Location,
+        interfaces: Vec<types::Interface>,
+        secure_boot: bool,
+        virtualized: bool,
+        serial_number: Option<String>,
+    ) -> Result<EncodedCall, Error>;
+
+    fn update_node_call(
+        &self,
+        cl: &Client,
+        node_id: u32,
+        farm_id: u32,
+        resources: types::Resources,
+        location: types::Location,
+        interfaces: Vec<types::Interface>,
+        secure_boot: bool,
+        virtualized: bool,
+        serial_number: Option<String>,
+    ) -> Result<EncodedCall, Error>;
+
+    fn delete_node_call(&self, cl: &Client, id: u32) -> Result<EncodedCall, Error>;
+
+    fn add_node_public_config_call(
+        &self,
+        cl: &Client,
+        farm_id: u32,
+        node_id: u32,
+        public_config: Option<types::PublicConfig>,
+    ) -> Result<EncodedCall, Error>;
+
+    // the node of the first `NodeStored` or `NodeUpdated` event emitted by the extrinsic
+    fn node_event(
+        &self,
+        cl: &Client,
+        receipt: &TxReceipt,
+    ) -> Result<Option<types::TfgridNode>, Error>;
+
+    fn report_uptime_call(&self, cl: &Client, uptime: u64) -> Result<EncodedCall, Error>;
+
+    // the node reports it is going up or down, the chain records the block it went
+    // down at
+    fn change_power_state_call(
+        &self,
+        cl: &Client,
+        state: types::PowerTarget,
+    ) -> Result<EncodedCall, Error>;
+
+    fn change_power_target_call(
+        &self,
+        cl: &Client,
+        node_id: u32,
+        target: types::PowerTarget,
+    ) -> Result<EncodedCall, Error>;
+
+    // the report of the first `NodeUptimeReported` event emitted by the extrinsic
+    fn uptime_event(
+        &self,
+        cl: &Client,
+        receipt: &TxReceipt,
+    ) -> Result<Option<types::UptimeReport>, Error>;
+
+    fn create_entity_call(
+        &self,
+        cl: &Client,
+        target: AccountId32,
+        name: String,
+        country: String,
+        city: String,
+        signature: String,
+    ) -> Result<EncodedCall, Error>;
+
+    fn update_entity_call(
+        &self,
+        cl: &Client,
+        name: String,
+        country: String,
+        city: String,
+    ) -> Result<EncodedCall, Error>;
+
+    fn delete_entity_call(&self, cl: &Client) -> Result<EncodedCall, Error>;
+
+    fn add_twin_entity_call(
+        &self,
+        cl: &Client,
+        twin_id: u32,
+        entity_id: u32,
+        signature: String,
+    ) -> Result<EncodedCall, Error>;
+
+    fn delete_twin_entity_call(
+        &self,
+        cl: &Client,
+        twin_id: u32,
+        entity_id: u32,
+    ) -> Result<EncodedCall, Error>;
+
+    // the entity of the first `EntityStored` or `EntityUpdated` event emitted by the extrinsic
+    fn entity_event(
+        &self,
+        cl: &Client,
+        receipt: &TxReceipt,
+    ) -> Result<Option<types::Entity>, Error>;
+
+    async fn create_twin(
+        &self,
+        cl: &Client,
+        signer: &dyn TfchainSigner,
+        spec: types::TwinSpec,
+        opts: &TxOptions,
+    ) -> Result<u32, Error>;
+
+    async fn update_twin(
+        &self,
+        cl: &Client,
+        signer: &dyn TfchainSigner,
+        spec: types::TwinSpec,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error>;
+
+    async fn sign_terms_and_conditions(
+        &self,
+        cl: &Client,
+        signer: &dyn TfchainSigner,
+        document_link: String,
+        document_hash: String,
+        opts: &TxOptions,
+    ) -> Result<TxReceipt, Error>;
+
+    async fn get_twin_by_id(
+        &self,
+        cl: &Client,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::Twin>, Error>;
+
+    async fn get_twin_id_by_account(
+        &self,
+        cl: &Client,
+        account: AccountId32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<u32>, Error>;
+
+    async fn get_contract_by_id(
+        &self,
+        cl: &Client,
+        id: u64,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::Contract>, Error>;
+
+    async fn get_node_by_id(
+        &self,
+        cl: &Client,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::TfgridNode>, Error>;
+
+    async fn get_farm_by_id(
+        &self,
+        cl: &Client,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::TfgridFarm>, Error>;
+
+    async fn get_entity_by_id(
+        &self,
+        cl: &Client,
+        id: u32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::Entity>, Error>;
+
+    async fn get_entity_id_by_name(
+        &self,
+        cl: &Client,
+        name: String,
+        at_block: Option<Hash>,
+    ) -> Result<Option<u32>, Error>;
+
+    async fn get_balance(
+        &self,
+        cl: &Client,
+        account: &AccountId32,
+        at_block: Option<Hash>,
+    ) -> Result<Option<types::SystemAccountInfo>, Error>;
+}
+
+// defines the `Runtime` enum from the list of generated runtime modules. The
+// `Dynamic` variant is always available and works against the node metadata.
+macro_rules! runtimes {
+    ($($name:ident: $variant:ident),+ $(,)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub enum Runtime {
+            $($variant,)+
+            Dynamic,
+        }
+
+        impl Runtime {
+            // the runtimes generated from the bundled metadata
+            pub const ALL: &'static [Runtime] = &[$(Runtime::$variant),+];
+
+            // the implementation of the runtime
+            pub fn implementation(&self) -> &'static dyn TfchainRuntime {
+                match self {
+                    $(Self::$variant => &$name::$variant,)+
+                    Self::Dynamic => &$crate::runtimes::dynamic::Dynamic,
+                }
+            }
+        }
+    };
+}
+
+impl FromStr for Runtime {
+    type Err = &'static str;
+
+    fn from_str(v: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .chain([Self::Dynamic])
+            .find(|runtime| runtime.implementation().name() == v)
+            .ok_or("unknown runtime")
+    }
+}
+
+impl Runtime {
+    // detects which of the bundled runtimes matches the metadata of the connected node.
+    // the match is done on the metadata hash of the generated code, so a node running
+    // a runtime that is not bundled in `artifacts/` is not matched.
+    pub fn detect(api: &OnlineClient<PolkadotConfig>) -> Option<Runtime> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|runtime| runtime.implementation().validate(api))
+    }
+
+    // the metadata the runtime code was generated from, `None` for `Runtime::Dynamic`
+    pub fn metadata(&self) -> Option<&'static [u8]> {
+        self.implementation().metadata()
+    }
+}
+
+// generates the code for a runtime from its metadata, and implements `TfchainRuntime`
+// for it. The invocations are generated by the build script for every file in `artifacts/`.
+macro_rules! tfchain_runtime {
+    // twins that have a relay and a public key
+    (@twin relay, $name:ident) => {
+        pub type Twin = TwinData<AccountId32>;
+
+        mod twin {
+            use super::$name::{
+                self, runtime_types::sp_core::bounded::bounded_vec::BoundedVec,
+                tfgrid_module::calls::{CreateTwin, UpdateTwin},
+            };
+            use super::types;
+            use crate::error::Error;
+            use subxt::tx::StaticTxPayload;
+
+            fn relay_and_pk(
+                spec: types::TwinSpec,
+            ) -> Result<(Option<BoundedVec<u8>>, Option<BoundedVec<u8>>), Error> {
+                if spec.ip.is_some() {
+                    return Err(Error::FeatureUnsupportedOnRuntime {
+                        runtime: stringify!($name),
+                        feature: "twin ip",
+                    });
+                }
+
+                Ok((
+                    spec.relay.map(|r| BoundedVec(r.into_bytes())),
+                    spec.pk.map(BoundedVec),
+                ))
+            }
+
+            pub fn create_twin_tx(spec: types::TwinSpec) -> Result<StaticTxPayload<CreateTwin>, Error> {
+                let (relay, pk) = relay_and_pk(spec)?;
+
+                Ok($name::tx().tfgrid_module().create_twin(relay, pk))
+            }
+
+            pub fn update_twin_tx(spec: types::TwinSpec) -> Result<StaticTxPayload<UpdateTwin>, Error> {
+                let (relay, pk) = relay_and_pk(spec)?;
+
+                Ok($name::tx().tfgrid_module().update_twin(relay, pk))
+            }
+        }
+    };
+
+    // twins that have an ip, the relay and public key are not supported
+    (@twin ip, $name:ident) => {
+        pub use $name::runtime_types::pallet_tfgrid::twin::TwinIp;
+
+        pub type Twin = TwinData<TwinIp, AccountId32>;
+
+        mod twin {
+            use super::$name::{
+                self, runtime_types::sp_core::bounded::bounded_vec::BoundedVec,
+                tfgrid_module::calls::{CreateTwin, UpdateTwin},
+            };
+            use super::types;
+            use crate::error::Error;
+            use subxt::tx::StaticTxPayload;
+
+            fn ip(spec: types::TwinSpec) -> Result<BoundedVec<u8>, Error> {
+                let unsupported = match (&spec.relay, &spec.pk) {
+                    (Some(_), _) => Some("twin relay"),
+                    (_, Some(_)) => Some("twin public key"),
+                    _ => None,
+                };
+                if let Some(feature) = unsupported {
+                    return Err(Error::FeatureUnsupportedOnRuntime {
+                        runtime: stringify!($name),
+                        feature,
+                    });
+                }
+
+                match spec.ip {
+                    Some(ip) => Ok(BoundedVec(ip.into_bytes())),
+                    None => Err(Error::MissingField {
+                        runtime: stringify!($name),
+                        field: "twin ip",
+                    }),
+                }
+            }
+
+            pub fn create_twin_tx(spec: types::TwinSpec) -> Result<StaticTxPayload<CreateTwin>, Error> {
+                Ok($name::tx().tfgrid_module().create_twin(ip(spec)?))
+            }
+
+            pub fn update_twin_tx(spec: types::TwinSpec) -> Result<StaticTxPayload<UpdateTwin>, Error> {
+                Ok($name::tx().tfgrid_module().update_twin(ip(spec)?))
+            }
+
+            #[cfg(test)]
+            mod tests {
+                use super::*;
+
+                #[test]
+                fn twins_need_an_ip() {
+                    let err = create_twin_tx(types::TwinSpec::default()).err().unwrap();
+                    assert!(
+                        matches!(err, Error::MissingField { field: "twin ip", .. }),
+                        "{}",
+                        err
+                    );
+                    assert!(update_twin_tx(types::TwinSpec::default()).is_err());
+
+                    let spec = types::TwinSpec::with_ip("::1".into());
+                    assert!(create_twin_tx(spec.clone()).is_ok());
+                    assert!(update_twin_tx(spec).is_ok());
+                }
+            }
+        }
+    };
+
+    // node power is managed on chain, nodes report their state and farmers set the target
+    (@power power, $name:ident) => {
+        mod power {
+            use super::$name::{
+                self,
+                runtime_types::tfchain_support::types::{Power, PowerState},
+            };
+            use super::types;
+            use crate::client::Client;
+            use crate::error::Error;
+            use crate::tx::EncodedCall;
+
+            fn power(target: types::PowerTarget) -> Power {
+                match target {
+                    types::PowerTarget::Up => Power::Up,
+                    types::PowerTarget::Down => Power::Down,
+                }
+            }
+
+            pub fn change_power_state_call(
+                cl: &Client,
+                state: types::PowerTarget,
+            ) -> Result<EncodedCall, Error> {
+                let tx = $name::tx().tfgrid_module().change_power_state(power(state));
+
+                Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+            }
+
+            pub fn change_power_target_call(
+                cl: &Client,
+                node_id: u32,
+                target: types::PowerTarget,
+            ) -> Result<EncodedCall, Error> {
+                let tx = $name::tx()
+                    .tfgrid_module()
+                    .change_power_target(node_id, power(target));
+
+                Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+            }
+
+            // the power of the node, nodes that never changed it are up
+            pub async fn node_power(
+                cl: &Client,
+                id: u32,
+                at_block: Option<types::Hash>,
+            ) -> Result<Option<types::Power>, Error> {
+                let power = cl
+                    .api
+                    .storage()
+                    .fetch_or_default(&$name::storage().tfgrid_module().node_power(id), at_block)
+                    .await?;
+
+                Ok(Some(types::Power {
+                    target: match power.target {
+                        Power::Up => types::PowerTarget::Up,
+                        Power::Down => types::PowerTarget::Down,
+                    },
+                    state: match power.state {
+                        PowerState::Up => types::PowerState::Up,
+                        PowerState::Down(block) => types::PowerState::Down(block),
+                    },
+                    last_uptime: 0,
+                }))
+            }
+        }
+    };
+
+    // node power is not known to the runtime
+    (@power nopower, $name:ident) => {
+        mod power {
+            use super::types;
+            use crate::client::Client;
+            use crate::error::Error;
+            use crate::tx::EncodedCall;
+
+            fn unsupported() -> Error {
+                Error::FeatureUnsupportedOnRuntime {
+                    runtime: stringify!($name),
+                    feature: "node power management",
+                }
+            }
+
+            pub fn change_power_state_call(
+                _cl: &Client,
+                _state: types::PowerTarget,
+            ) -> Result<EncodedCall, Error> {
+                Err(unsupported())
+            }
+
+            pub fn change_power_target_call(
+                _cl: &Client,
+                _node_id: u32,
+                _target: types::PowerTarget,
+            ) -> Result<EncodedCall, Error> {
+                Err(unsupported())
+            }
+
+            pub async fn node_power(
+                _cl: &Client,
+                _id: u32,
+                _at_block: Option<types::Hash>,
+            ) -> Result<Option<types::Power>, Error> {
+                Ok(None)
+            }
+        }
+    };
+
+    ($name:ident, $variant:ident, $path:tt, $twin:ident, $power:ident) => {
+        pub mod $name {
+            #[subxt::subxt(runtime_metadata_path = $path)]
+            pub mod $name {
+                #[subxt(substitute_type = "frame_support::storage::bounded_vec::BoundedVec")]
+                use ::sp_std::vec::Vec;
+                #[subxt(substitute_type = "bounded::bounded::BoundedVec")]
+                use ::sp_std::vec::Vec;
+            }
+            use super::types;
+            pub use $name::runtime_types::frame_system::AccountInfo;
+            pub use $name::runtime_types::pallet_balances::AccountData;
+            pub use $name::runtime_types::pallet_smart_contract::types::Contract;
+            pub use $name::runtime_types::pallet_tfgrid::{
+                farm::FarmName,
+                interface::{InterfaceIp, InterfaceMac, InterfaceName},
+                node::{CityName, CountryName, Location, SerialNumber},
+                types::{Entity as EntityData, LocationInput, Twin as TwinData},
+            };
+            pub use $name::runtime_types::tfchain_support::resources::Resources as NodeResources;
+            pub use $name::runtime_types::tfchain_support::types::{
+                Farm as FarmData, Interface, Node as NodeData, PublicConfig,
+                PublicIP as PublicIpData, IP4, IP6,
+            };
+            use $name::runtime_types::sp_core::bounded::bounded_vec::BoundedVec;
+            use subxt::ext::sp_runtime::AccountId32;
+            use subxt::{OnlineClient, PolkadotConfig};
+
+            pub type Farm = FarmData<FarmName>;
+            pub type Entity = EntityData<AccountId32, CityName, CountryName>;
+
+            pub type InterfaceOf = Interface<InterfaceName, InterfaceMac, BoundedVec<InterfaceIp>>;
+            pub type Node = NodeData<Location, InterfaceOf, SerialNumber>;
+
+            pub type SystemAccountInfo = AccountInfo<u32, AccountData<u128>>;
+
+            pub const METADATA: &[u8] =
+                include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path));
+
+            use crate::client::Client;
+            use crate::error::Error;
+            use crate::signer::TfchainSigner;
+            use crate::tx::{EncodedCall, TxOptions, TxReceipt, WaitFor};
+
+            pub use $name::tft_bridge_module::events::BurnTransactionReady;
+            pub use $name::tft_bridge_module::events::BurnTransactionSignatureAdded;
+            pub use $name::tft_bridge_module::events::MintTransactionProposed;
+
+            tfchain_runtime!(@twin $twin, $name);
+            tfchain_runtime!(@power $power, $name);
+            impl_conversions!($name, $twin);
+
+            fn sign_tandc_tx(
+                document_link: String,
+                document_hash: String,
+            ) -> subxt::tx::StaticTxPayload<$name::tfgrid_module::calls::UserAcceptTc> {
+                $name::tx().tfgrid_module().user_accept_tc(
+                    BoundedVec(document_link.as_bytes().to_vec()),
+                    BoundedVec(document_hash.as_bytes().to_vec()),
+                )
+            }
+
+            // the node fields as taken by `create_node` and `update_node`
+            fn node_input(
+                resources: types::Resources,
+                location: types::Location,
+                interfaces: Vec<types::Interface>,
+            ) -> (
+                NodeResources,
+                LocationInput<BoundedVec<u8>, BoundedVec<u8>, BoundedVec<u8>, BoundedVec<u8>>,
+                BoundedVec<Interface<BoundedVec<u8>, BoundedVec<u8>, BoundedVec<BoundedVec<u8>>>>,
+            ) {
+                let resources = NodeResources {
+                    hru: resources.hru,
+                    sru: resources.sru,
+                    cru: resources.cru,
+                    mru: resources.mru,
+                };
+                let location = LocationInput {
+                    city: BoundedVec(location.city.into_bytes()),
+                    country: BoundedVec(location.country.into_bytes()),
+                    latitude: BoundedVec(location.latitude.into_bytes()),
+                    longitude: BoundedVec(location.longitude.into_bytes()),
+                    // the bounds of the fields are type parameters on chain
+                    __subxt_unused_type_params: ::core::marker::PhantomData,
+                };
+                let interfaces = interfaces
+                    .into_iter()
+                    .map(|intf| Interface {
+                        name: BoundedVec(intf.name.into_bytes()),
+                        mac: BoundedVec(intf.mac.into_bytes()),
+                        ips: BoundedVec(
+                            intf.ips
+                                .into_iter()
+                                .map(|ip| BoundedVec(ip.into_bytes()))
+                                .collect(),
+                        ),
+                    })
+                    .collect();
+
+                (resources, location, BoundedVec(interfaces))
+            }
+
+            pub struct $variant;
+
+            #[async_trait::async_trait]
+            impl super::TfchainRuntime for $variant {
+                fn name(&self) -> &'static str {
+                    stringify!($name)
+                }
+
+                fn metadata(&self) -> Option<&'static [u8]> {
+                    Some(METADATA)
+                }
+
+                fn validate(&self, api: &OnlineClient<PolkadotConfig>) -> bool {
+                    $name::validate_codegen(api).is_ok()
+                }
+
+                fn create_twin_call(
+                    &self,
+                    cl: &Client,
+                    spec: types::TwinSpec,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = twin::create_twin_tx(spec)?;
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn update_twin_call(
+                    &self,
+                    cl: &Client,
+                    spec: types::TwinSpec,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = twin::update_twin_tx(spec)?;
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn sign_terms_and_conditions_call(
+                    &self,
+                    cl: &Client,
+                    document_link: String,
+                    document_hash: String,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = sign_tandc_tx(document_link, document_hash);
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn create_farm_call(
+                    &self,
+                    cl: &Client,
+                    name: String,
+                    public_ips: Vec<types::FarmPublicIPInput>,
+                ) -> Result<EncodedCall, Error> {
+                    let public_ips = public_ips
+                        .into_iter()
+                        .map(|ip| IP4 {
+                            ip: BoundedVec(ip.ip.into_bytes()),
+                            gw: BoundedVec(ip.gateway.into_bytes()),
+                        })
+                        .collect();
+                    let tx = $name::tx()
+                        .tfgrid_module()
+                        .create_farm(BoundedVec(name.into_bytes()), BoundedVec(public_ips));
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn update_farm_call(
+                    &self,
+                    cl: &Client,
+                    id: u32,
+                    name: String,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx()
+                        .tfgrid_module()
+                        .update_farm(id, BoundedVec(name.into_bytes()));
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn add_farm_ip_call(
+                    &self,
+                    cl: &Client,
+                    id: u32,
+                    ip: String,
+                    gateway: String,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx().tfgrid_module().add_farm_ip(
+                        id,
+                        BoundedVec(ip.into_bytes()),
+                        BoundedVec(gateway.into_bytes()),
+                    );
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn remove_farm_ip_call(
+                    &self,
+                    cl: &Client,
+                    id: u32,
+                    ip: String,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx()
+                        .tfgrid_module()
+                        .remove_farm_ip(id, BoundedVec(ip.into_bytes()));
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn add_stellar_payout_v2address_call(
+                    &self,
+                    cl: &Client,
+                    farm_id: u32,
+                    stellar_address: String,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx()
+                        .tfgrid_module()
+                        .add_stellar_payout_v2address(farm_id, stellar_address.into_bytes());
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn set_farm_dedicated_call(
+                    &self,
+                    cl: &Client,
+                    farm_id: u32,
+                    dedicated: bool,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx()
+                        .tfgrid_module()
+                        .set_farm_dedicated(farm_id, dedicated);
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn farm_event(
+                    &self,
+                    _cl: &Client,
+                    receipt: &TxReceipt,
+                ) -> Result<Option<types::TfgridFarm>, Error> {
+                    use $name::tfgrid_module::events::{FarmStored, FarmUpdated};
+
+                    if let Some(event) = receipt.find_first::<FarmStored>()? {
+                        return Ok(Some(types::TfgridFarm::from(event.0)));
+                    }
+
+                    Ok(receipt
+                        .find_first::<FarmUpdated>()?
+                        .map(|event| types::TfgridFarm::from(event.0)))
+                }
+
+                fn create_node_call(
+                    &self,
+                    cl: &Client,
+                    farm_id: u32,
+                    resources: types::Resources,
+                    location: types::Location,
+                    interfaces: Vec<types::Interface>,
+                    secure_boot: bool,
+                    virtualized: bool,
+                    serial_number: Option<String>,
+                ) -> Result<EncodedCall, Error> {
+                    let (resources, location, interfaces) =
+                        node_input(resources, location, interfaces);
+                    let tx = $name::tx().tfgrid_module().create_node(
+                        farm_id,
+                        resources,
+                        location,
+                        interfaces,
+                        secure_boot,
+                        virtualized,
+                        serial_number.map(|s| BoundedVec(s.into_bytes())),
+                    );
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn update_node_call(
+                    &self,
+                    cl: &Client,
+                    node_id: u32,
+                    farm_id: u32,
+                    resources: types::Resources,
+                    location: types::Location,
+                    interfaces: Vec<types::Interface>,
+                    secure_boot: bool,
+                    virtualized: bool,
+                    serial_number: Option<String>,
+                ) -> Result<EncodedCall, Error> {
+                    let (resources, location, interfaces) =
+                        node_input(resources, location, interfaces);
+                    let tx = $name::tx().tfgrid_module().update_node(
+                        node_id,
+                        farm_id,
+                        resources,
+                        location,
+                        interfaces,
+                        secure_boot,
+                        virtualized,
+                        serial_number.map(|s| BoundedVec(s.into_bytes())),
+                    );
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn delete_node_call(&self, cl: &Client, id: u32) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx().tfgrid_module().delete_node(id);
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn add_node_public_config_call(
+                    &self,
+                    cl: &Client,
+                    farm_id: u32,
+                    node_id: u32,
+                    public_config: Option<types::PublicConfig>,
+                ) -> Result<EncodedCall, Error> {
+                    let public_config = public_config.map(|config| PublicConfig {
+                        ip4: IP4 {
+                            ip: BoundedVec(config.ip4.ip.into_bytes()),
+                            gw: BoundedVec(config.ip4.gw.into_bytes()),
+                        },
+                        ip6: config.ip6.map(|ip6| IP6 {
+                            ip: BoundedVec(ip6.ip.into_bytes()),
+                            gw: BoundedVec(ip6.gw.into_bytes()),
+                        }),
+                        domain: config.domain.map(|domain| BoundedVec(domain.into_bytes())),
+                    });
+                    let tx = $name::tx().tfgrid_module().add_node_public_config(
+                        farm_id,
+                        node_id,
+                        public_config,
+                    );
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn node_event(
+                    &self,
+                    _cl: &Client,
+                    receipt: &TxReceipt,
+                ) -> Result<Option<types::TfgridNode>, Error> {
+                    use $name::tfgrid_module::events::{NodeStored, NodeUpdated};
+
+                    if let Some(event) = receipt.find_first::<NodeStored>()? {
+                        return Ok(Some(types::TfgridNode::from(event.0)));
+                    }
+
+                    Ok(receipt
+                        .find_first::<NodeUpdated>()?
+                        .map(|event| types::TfgridNode::from(event.0)))
+                }
+
+                fn report_uptime_call(&self, cl: &Client, uptime: u64) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx().tfgrid_module().report_uptime(uptime);
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn change_power_state_call(
+                    &self,
+                    cl: &Client,
+                    state: types::PowerTarget,
+                ) -> Result<EncodedCall, Error> {
+                    power::change_power_state_call(cl, state)
+                }
+
+                fn change_power_target_call(
+                    &self,
+                    cl: &Client,
+                    node_id: u32,
+                    target: types::PowerTarget,
+                ) -> Result<EncodedCall, Error> {
+                    power::change_power_target_call(cl, node_id, target)
+                }
+
+                fn uptime_event(
+                    &self,
+                    _cl: &Client,
+                    receipt: &TxReceipt,
+                ) -> Result<Option<types::UptimeReport>, Error> {
+                    use $name::tfgrid_module::events::NodeUptimeReported;
+
+                    Ok(receipt
+                        .find_first::<NodeUptimeReported>()?
+                        .map(|event| types::UptimeReport {
+                            node_id: event.0,
+                            timestamp: event.1,
+                            uptime: event.2,
+                        }))
+                }
+
+                fn create_entity_call(
+                    &self,
+                    cl: &Client,
+                    target: AccountId32,
+                    name: String,
+                    country: String,
+                    city: String,
+                    signature: String,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx().tfgrid_module().create_entity(
+                        target,
+                        name.into_bytes(),
+                        BoundedVec(country.into_bytes()),
+                        BoundedVec(city.into_bytes()),
+                        signature.into_bytes(),
+                    );
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn update_entity_call(
+                    &self,
+                    cl: &Client,
+                    name: String,
+                    country: String,
+                    city: String,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx().tfgrid_module().update_entity(
+                        name.into_bytes(),
+                        BoundedVec(country.into_bytes()),
+                        BoundedVec(city.into_bytes()),
+                    );
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn delete_entity_call(&self, cl: &Client) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx().tfgrid_module().delete_entity();
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn add_twin_entity_call(
+                    &self,
+                    cl: &Client,
+                    twin_id: u32,
+                    entity_id: u32,
+                    signature: String,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx().tfgrid_module().add_twin_entity(
+                        twin_id,
+                        entity_id,
+                        signature.into_bytes(),
+                    );
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn delete_twin_entity_call(
+                    &self,
+                    cl: &Client,
+                    twin_id: u32,
+                    entity_id: u32,
+                ) -> Result<EncodedCall, Error> {
+                    let tx = $name::tx()
+                        .tfgrid_module()
+                        .delete_twin_entity(twin_id, entity_id);
+
+                    Ok(EncodedCall::new(cl.api.tx().call_data(&tx)?))
+                }
+
+                fn entity_event(
+                    &self,
+                    _cl: &Client,
+                    receipt: &TxReceipt,
+                ) -> Result<Option<types::Entity>, Error> {
+                    use $name::tfgrid_module::events::{EntityStored, EntityUpdated};
+
+                    if let Some(event) = receipt.find_first::<EntityStored>()? {
+                        return Ok(Some(types::Entity::from(event.0)));
+                    }
+
+                    Ok(receipt
+                        .find_first::<EntityUpdated>()?
+                        .map(|event| types::Entity::from(event.0)))
+                }
+
+                async fn create_twin(
+                    &self,
+                    cl: &Client,
+                    signer: &dyn TfchainSigner,
+                    spec: types::TwinSpec,
+                    opts: &TxOptions,
+                ) -> Result<u32, Error> {
+                    let create_twin_tx = twin::create_twin_tx(spec)?;
+
+                    let create_twin = cl.submit(&create_twin_tx, signer, opts).await?;
+
+                    let twin_create_event =
+                        create_twin.find_first::<$name::tfgrid_module::events::TwinStored>()?;
+
+                    if let Some(event) = twin_create_event {
+                        Ok(event.0.id)
+                    } else {
+                        Err(Error::event_not_found::<
+                            $name::tfgrid_module::events::TwinStored,
+                        >())
+                    }
+                }
+
+                async fn update_twin(
+                    &self,
+                    cl: &Client,
+                    signer: &dyn TfchainSigner,
+                    spec: types::TwinSpec,
+                    opts: &TxOptions,
+                ) -> Result<TxReceipt, Error> {
+                    let update_twin_tx = twin::update_twin_tx(spec)?;
+
+                    let update_twin = cl.submit(&update_twin_tx, signer, opts).await?;
+
+                    let twin_update_event =
+                        update_twin.find_first::<$name::tfgrid_module::events::TwinUpdated>()?;
+
+                    if twin_update_event.is_some() || opts.wait_for == WaitFor::Broadcast {
+                        Ok(update_twin)
+                    } else {
+                        Err(Error::event_not_found::<
+                            $name::tfgrid_module::events::TwinUpdated,
+                        >())
+                    }
+                }
+
+                async fn sign_terms_and_conditions(
+                    &self,
+                    cl: &Client,
+                    signer: &dyn TfchainSigner,
+                    document_link: String,
+                    document_hash: String,
+                    opts: &TxOptions,
+                ) -> Result<TxReceipt, Error> {
+                    let sign_tandc_tx = sign_tandc_tx(document_link, document_hash);
+
+                    cl.submit(&sign_tandc_tx, signer, opts).await
+                }
+
+                async fn get_twin_by_id(
+                    &self,
+                    cl: &Client,
+                    id: u32,
+                    at_block: Option<types::Hash>,
+                ) -> Result<Option<types::Twin>, Error> {
+                    Ok(cl
+                        .api
+                        .storage()
+                        .fetch(&$name::storage().tfgrid_module().twins(id), at_block)
+                        .await?
+                        .map(types::Twin::from))
+                }
+
+                async fn get_twin_id_by_account(
+                    &self,
+                    cl: &Client,
+                    account: AccountId32,
+                    at_block: Option<types::Hash>,
+                ) -> Result<Option<u32>, Error> {
+                    Ok(cl
+                        .api
+                        .storage()
+                        .fetch(
+                            &$name::storage()
+                                .tfgrid_module()
+                                .twin_id_by_account_id(account),
+                            at_block,
+                        )
+                        .await?)
+                }
+
+                async fn get_contract_by_id(
+                    &self,
+                    cl: &Client,
+                    id: u64,
+                    at_block: Option<types::Hash>,
+                ) -> Result<Option<types::Contract>, Error> {
+                    Ok(cl
+                        .api
+                        .storage()
+                        .fetch(
+                            &$name::storage().smart_contract_module().contracts(id),
+                            at_block,
+                        )
+                        .await?
+                        .map(types::Contract::from))
+                }
+
+                async fn get_node_by_id(
+                    &self,
+                    cl: &Client,
+                    id: u32,
+                    at_block: Option<types::Hash>,
+                ) -> Result<Option<types::TfgridNode>, Error> {
+                    let mut node = match cl
+                        .api
+                        .storage()
+                        .fetch(&$name::storage().tfgrid_module().nodes(id), at_block)
+                        .await?
+                    {
+                        Some(node) => types::TfgridNode::from(node),
+                        None => return Ok(None),
+                    };
+
+                    if let Some(power) = power::node_power(cl, id, at_block).await? {
+                        node.power = power;
+                    }
+
+                    Ok(Some(node))
+                }
+
+                async fn get_farm_by_id(
+                    &self,
+                    cl: &Client,
+                    id: u32,
+                    at_block: Option<types::Hash>,
+                ) -> Result<Option<types::TfgridFarm>, Error> {
+                    Ok(cl
+                        .api
+                        .storage()
+                        .fetch(&$name::storage().tfgrid_module().farms(id), at_block)
+                        .await?
+                        .map(types::TfgridFarm::from))
+                }
+
+                async fn get_entity_by_id(
+                    &self,
+                    cl: &Client,
+                    id: u32,
+                    at_block: Option<types::Hash>,
+                ) -> Result<Option<types::Entity>, Error> {
+                    Ok(cl
+                        .api
+                        .storage()
+                        .fetch(&$name::storage().tfgrid_module().entities(id), at_block)
+                        .await?
+                        .map(types::Entity::from))
+                }
+
+                // entity IDs start at 1, a name without entity is stored as 0
+                async fn get_entity_id_by_name(
+                    &self,
+                    cl: &Client,
+                    name: String,
+                    at_block: Option<types::Hash>,
+                ) -> Result<Option<u32>, Error> {
+                    let id = cl
+                        .api
+                        .storage()
+                        .fetch_or_default(
+                            &$name::storage()
+                                .tfgrid_module()
+                                .entity_id_by_name(name.into_bytes()),
+                            at_block,
+                        )
+                        .await?;
+
+                    Ok(Some(id).filter(|id| *id != 0))
+                }
+
+                async fn get_balance(
+                    &self,
+                    cl: &Client,
+                    account: &AccountId32,
+                    at_block: Option<types::Hash>,
+                ) -> Result<Option<types::SystemAccountInfo>, Error> {
+                    Ok(cl
+                        .api
+                        .storage()
+                        .fetch(&$name::storage().system().account(account), at_block)
+                        .await?
+                        .map(types::SystemAccountInfo::from))
+                }
+            }
+        }
+    };
+}
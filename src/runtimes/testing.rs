@@ -0,0 +1,34 @@
+use subxt::{
+    ext::{codec::Decode, frame_metadata::RuntimeMetadataPrefixed},
+    metadata::Metadata,
+};
+
+// Fixtures for the tests, built from the metadata of the networks in `artifacts/`.
+
+pub(crate) fn metadata_bytes(network: &str) -> Vec<u8> {
+    let path = format!("{}/artifacts/{}.scale", env!("CARGO_MANIFEST_DIR"), network);
+    std::fs::read(&path).unwrap_or_else(|_| panic!("{} exists", path))
+}
+
+pub(crate) fn metadata(network: &str) -> Metadata {
+    RuntimeMetadataPrefixed::decode(&mut metadata_bytes(network).as_slice())
+        .expect("artifact is metadata")
+        .try_into()
+        .expect("metadata is v14")
+}
+
+// answers the requests subxt makes to build a client for the network, `None` for
+// the other requests
+pub(crate) fn client_response(network: &str, method: &str) -> Option<serde_json::Value> {
+    match method {
+        "chain_getBlockHash" => Some(serde_json::json!(format!("0x{}", "00".repeat(32)))),
+        "state_getRuntimeVersion" => {
+            Some(serde_json::json!({"specVersion": 1, "transactionVersion": 1}))
+        }
+        "state_getMetadata" => Some(serde_json::json!(format!(
+            "0x{}",
+            hex::encode(metadata_bytes(network))
+        ))),
+        _ => None,
+    }
+}
@@ -0,0 +1,317 @@
+use crate::client::{KeyPair, KeyType};
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+use subxt::ext::{
+    sp_core::{crypto::Ss58Codec, ed25519, sr25519},
+    sp_runtime::{AccountId32, MultiSignature},
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+// TfchainSigner signs the extrinsics submitted by the client. Signing is async so
+// the keys can live outside of the client process, for example in a signing daemon.
+#[async_trait::async_trait]
+pub trait TfchainSigner: Send + Sync {
+    // the account the extrinsics are sent from
+    fn account_id(&self) -> AccountId32;
+
+    // signs the encoded signer payload of an extrinsic
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error>;
+}
+
+#[async_trait::async_trait]
+impl TfchainSigner for KeyPair {
+    fn account_id(&self) -> AccountId32 {
+        KeyPair::account_id(self)
+    }
+
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+        Ok(signature(self.key_type(), &KeyPair::sign(self, payload)))
+    }
+}
+
+// builds the signature for the key type from its raw bytes
+pub(crate) fn signature(k: KeyType, sig: &[u8; 64]) -> MultiSignature {
+    match k {
+        KeyType::Sr25519 => sr25519::Signature::from_raw(*sig).into(),
+        KeyType::Ed25519 => ed25519::Signature::from_raw(*sig).into(),
+    }
+}
+
+// SignerEndpoint is where a signing service listens
+#[derive(Debug, Clone)]
+pub enum SignerEndpoint {
+    // path of a unix socket, requests and responses are sent as one JSON document per line
+    Unix(PathBuf),
+    // url of the service, requests are POSTed as JSON to it
+    Http(String),
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    account: String,
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+// RemoteSigner asks a signing service to sign the extrinsics. For every extrinsic it
+// sends `{"account": "<ss58 address>", "payload": "0x<hex>"}` and expects either
+// `{"signature": "0x<hex>"}` or `{"error": "<reason>"}` back.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    endpoint: SignerEndpoint,
+    account: AccountId32,
+    key_type: KeyType,
+}
+
+impl RemoteSigner {
+    pub fn new(endpoint: SignerEndpoint, account: AccountId32, key_type: KeyType) -> Self {
+        RemoteSigner {
+            endpoint,
+            account,
+            key_type,
+        }
+    }
+
+    async fn request(&self, body: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match &self.endpoint {
+            SignerEndpoint::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path)
+                    .await
+                    .map_err(|err| Error::Signer(err.to_string()))?;
+                let (reader, mut writer) = stream.into_split();
+
+                writer
+                    .write_all(&[body.as_slice(), b"\n"].concat())
+                    .await
+                    .map_err(|err| Error::Signer(err.to_string()))?;
+
+                let mut line = String::new();
+                BufReader::new(reader)
+                    .read_line(&mut line)
+                    .await
+                    .map_err(|err| Error::Signer(err.to_string()))?;
+
+                Ok(line.into_bytes())
+            }
+            SignerEndpoint::Http(url) => {
+                let request = hyper::Request::post(url)
+                    .header("content-type", "application/json")
+                    .body(hyper::Body::from(body))
+                    .map_err(|err| Error::Signer(err.to_string()))?;
+
+                let response = hyper::Client::new()
+                    .request(request)
+                    .await
+                    .map_err(|err| Error::Signer(err.to_string()))?;
+
+                let body = hyper::body::to_bytes(response.into_body())
+                    .await
+                    .map_err(|err| Error::Signer(err.to_string()))?;
+
+                Ok(body.to_vec())
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TfchainSigner for RemoteSigner {
+    fn account_id(&self) -> AccountId32 {
+        self.account.clone()
+    }
+
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+        let request = SignRequest {
+            account: self.account.to_ss58check(),
+            payload: format!("0x{}", hex::encode(payload)),
+        };
+        let body = serde_json::to_vec(&request).map_err(|err| Error::Signer(err.to_string()))?;
+
+        let response: SignResponse = serde_json::from_slice(&self.request(body).await?)
+            .map_err(|err| Error::Signer(format!("invalid response: {}", err)))?;
+
+        let sig = match (response.signature, response.error) {
+            (_, Some(err)) => return Err(Error::Signer(err)),
+            (Some(sig), None) => sig,
+            (None, None) => return Err(Error::Signer("empty response".into())),
+        };
+
+        let sig: [u8; 64] = hex::decode(sig.trim_start_matches("0x"))
+            .ok()
+            .and_then(|sig| sig.try_into().ok())
+            .ok_or_else(|| Error::Signer("invalid signature".into()))?;
+
+        Ok(signature(self.key_type, &sig))
+    }
+}
+
+// MockSigner signs in process with a key pair and records the payloads it signed.
+// It can be set to reject requests to test how callers handle signer failures.
+pub struct MockSigner {
+    pair: KeyPair,
+    signed: Mutex<Vec<Vec<u8>>>,
+    reject: AtomicBool,
+}
+
+impl MockSigner {
+    pub fn new(pair: KeyPair) -> Self {
+        MockSigner {
+            pair,
+            signed: Mutex::new(vec![]),
+            reject: AtomicBool::new(false),
+        }
+    }
+
+    pub fn set_reject(&self, reject: bool) {
+        self.reject.store(reject, Ordering::SeqCst);
+    }
+
+    // the payloads signed so far
+    pub fn signed(&self) -> Vec<Vec<u8>> {
+        self.signed.lock().expect("lock is not poisoned").clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl TfchainSigner for MockSigner {
+    fn account_id(&self) -> AccountId32 {
+        self.pair.account_id()
+    }
+
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+        if self.reject.load(Ordering::SeqCst) {
+            return Err(Error::Signer("rejected by mock signer".into()));
+        }
+
+        self.signed
+            .lock()
+            .expect("lock is not poisoned")
+            .push(payload.to_vec());
+
+        TfchainSigner::sign(&self.pair, payload).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subxt::ext::sp_runtime::traits::Verify;
+    use tokio::{net::UnixListener, task::JoinHandle};
+
+    const PHRASE: &str = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+
+    // a directory removed when the test ends
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let name = format!("tfchain-signer-{}", rand::random::<u64>());
+            let dir = std::env::temp_dir().join(name);
+            std::fs::create_dir(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // a signing service answering one request, the response is built from the
+    // request. Returns the request it got.
+    fn service(
+        dir: &TempDir,
+        respond: impl FnOnce(&serde_json::Value) -> String + Send + 'static,
+    ) -> (SignerEndpoint, JoinHandle<serde_json::Value>) {
+        let path = dir.0.join("signer.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let service = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+
+            let mut line = String::new();
+            BufReader::new(reader).read_line(&mut line).await.unwrap();
+            let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+            writer
+                .write_all(format!("{}\n", respond(&request)).as_bytes())
+                .await
+                .unwrap();
+            request
+        });
+
+        (SignerEndpoint::Unix(path), service)
+    }
+
+    #[tokio::test]
+    async fn signs_over_a_unix_socket() {
+        let dir = TempDir::new();
+        let pair = KeyPair::from_phrase(KeyType::Sr25519, PHRASE, None).unwrap();
+        let (endpoint, service) = service(&dir, {
+            let pair = pair.clone();
+            move |request| {
+                let payload = request["payload"]
+                    .as_str()
+                    .unwrap()
+                    .trim_start_matches("0x");
+                let sig = pair.sign(&hex::decode(payload).unwrap());
+                serde_json::json!({ "signature": format!("0x{}", hex::encode(sig)) }).to_string()
+            }
+        });
+
+        let signer = RemoteSigner::new(endpoint, pair.account_id(), KeyType::Sr25519);
+        let sig = signer.sign(b"payload").await.unwrap();
+
+        assert_eq!(
+            service.await.unwrap(),
+            serde_json::json!({
+                "account": pair.account_id().to_ss58check(),
+                "payload": "0x7061796c6f6164",
+            })
+        );
+        assert!(matches!(sig, MultiSignature::Sr25519(_)));
+        assert!(sig.verify(&b"payload"[..], &pair.account_id()));
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_responses() {
+        let sig = format!("0x{}", "11".repeat(64));
+        for (response, expected) in [
+            (
+                r#"{"error": "account is locked"}"#.to_string(),
+                "account is locked",
+            ),
+            (r#"{"signature": "0x0102"}"#.into(), "invalid signature"),
+            (
+                format!(r#"{{"signature": "{}00"}}"#, sig),
+                "invalid signature",
+            ),
+            (r#"{"signature": "0xzz"}"#.into(), "invalid signature"),
+            ("{}".into(), "empty response"),
+            ("signature".into(), "invalid response"),
+        ] {
+            let dir = TempDir::new();
+            let (endpoint, _service) = service(&dir, move |_| response);
+            let signer = RemoteSigner::new(endpoint, AccountId32::new([1; 32]), KeyType::Ed25519);
+
+            match signer.sign(b"payload").await {
+                Err(Error::Signer(err)) => assert!(err.contains(expected), "{}", err),
+                res => panic!("unexpected result {:?}", res),
+            }
+        }
+    }
+}
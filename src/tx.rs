@@ -0,0 +1,680 @@
+use crate::client::{verify, KeyType};
+use crate::error::Error;
+use crate::runtimes::types::Hash;
+use crate::signer::{self, TfchainSigner};
+use serde::Deserialize;
+use std::time::Duration;
+use subxt::{
+    error::DispatchError,
+    events::{EventDetails, StaticEvent},
+    ext::{
+        codec::{Compact, Decode, Encode},
+        frame_metadata::RuntimeMetadataPrefixed,
+        scale_value::Composite,
+        sp_core::blake2_256,
+        sp_runtime::{
+            generic::Era, transaction_validity::TransactionValidityError, AccountId32,
+            MultiAddress, MultiSignature,
+        },
+    },
+    metadata::Metadata,
+    rpc::RuntimeVersion,
+    tx::{
+        ExtrinsicParams, PlainTip, PolkadotExtrinsicParams, PolkadotExtrinsicParamsBuilder,
+        TxPayload,
+    },
+    OfflineClient, PolkadotConfig,
+};
+
+pub type ExtrinsicParamsOf = PolkadotExtrinsicParams<PolkadotConfig>;
+pub type ExtrinsicParamsBuilderOf = PolkadotExtrinsicParamsBuilder<PolkadotConfig>;
+
+// WaitFor is how far a submitted extrinsic has to get before the call returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WaitFor {
+    // the node accepted the extrinsic in its pool
+    Broadcast,
+    // the extrinsic was included in a block, which can still be retracted
+    InBlock,
+    // the block including the extrinsic was finalized
+    #[default]
+    Finalized,
+}
+
+// TxOptions are the options of the calls that submit an extrinsic. The default waits
+// for the extrinsic to be finalized, without timeout, tip or mortality.
+#[derive(Debug, Clone, Default)]
+pub struct TxOptions {
+    pub wait_for: WaitFor,
+    // fails with `Error::Timeout` if the extrinsic did not get to `wait_for` in time
+    pub timeout: Option<Duration>,
+    // number of blocks the extrinsic stays valid for, it never expires if `None`
+    pub mortality: Option<u64>,
+    // tip paid to the block author on top of the fee
+    pub tip: u128,
+}
+
+// TxReceipt describes a submitted extrinsic. The block, events and fee are only
+// known once the extrinsic was included in a block, they are empty when only
+// waiting for it to be broadcast.
+#[derive(Debug, Clone)]
+pub struct TxReceipt {
+    pub extrinsic_hash: Hash,
+    pub block_hash: Option<Hash>,
+    pub block_number: Option<u32>,
+    pub extrinsic_index: Option<u32>,
+    // the events emitted by the extrinsic
+    pub events: Vec<EventDetails>,
+    // the fee paid for the extrinsic, tip included
+    pub fee: Option<u128>,
+    pub finalized: bool,
+}
+
+impl TxReceipt {
+    pub(crate) fn broadcast(extrinsic_hash: Hash) -> Self {
+        TxReceipt {
+            extrinsic_hash,
+            block_hash: None,
+            block_number: None,
+            extrinsic_index: None,
+            events: vec![],
+            fee: None,
+            finalized: false,
+        }
+    }
+
+    // returns the first event of the given type emitted by the extrinsic
+    pub fn find_first<E: StaticEvent>(&self) -> Result<Option<E>, Error> {
+        for ev in &self.events {
+            if let Some(ev) = ev.as_event::<E>().map_err(subxt::Error::from)? {
+                return Ok(Some(ev));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn has<E: StaticEvent>(&self) -> Result<bool, Error> {
+        Ok(self.find_first::<E>()?.is_some())
+    }
+}
+
+// the fee is read from the `TransactionFeePaid` event of the transaction payment pallet
+pub(crate) fn fee_paid(events: &[EventDetails]) -> Option<u128> {
+    let event = events.iter().find(|ev| {
+        ev.pallet_name() == "TransactionPayment" && ev.variant_name() == "TransactionFeePaid"
+    })?;
+
+    match event.field_values().ok()? {
+        Composite::Named(fields) => fields
+            .into_iter()
+            .find(|(name, _)| name == "actual_fee")
+            .and_then(|(_, fee)| fee.as_u128()),
+        Composite::Unnamed(_) => None,
+    }
+}
+
+// SignerPayload holds everything that goes into a signed extrinsic except the
+// signature, it is what gets handed to a signer. The payload can be exported as hex
+// or as a UOS frame to be signed by another party, and the signature attached later
+// with `SignerPayload::into_signed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerPayload {
+    call_data: Vec<u8>,
+    extra: Vec<u8>,
+    additional: Vec<u8>,
+}
+
+impl SignerPayload {
+    pub fn new(call_data: Vec<u8>, params: ExtrinsicParamsOf) -> Self {
+        let mut extra = vec![];
+        params.encode_extra_to(&mut extra);
+        let mut additional = vec![];
+        params.encode_additional_to(&mut additional);
+
+        SignerPayload {
+            call_data,
+            extra,
+            additional,
+        }
+    }
+
+    // rebuilds a payload saved with `call_data`, `extra` and `additional`
+    pub fn from_parts(call_data: Vec<u8>, extra: Vec<u8>, additional: Vec<u8>) -> Self {
+        SignerPayload {
+            call_data,
+            extra,
+            additional,
+        }
+    }
+
+    pub fn call_data(&self) -> &[u8] {
+        &self.call_data
+    }
+
+    // the signed extensions included in the extrinsic (era, nonce and tip)
+    pub fn extra(&self) -> &[u8] {
+        &self.extra
+    }
+
+    // the signed extensions only included in the signature (runtime versions,
+    // genesis hash and checkpoint block hash)
+    pub fn additional(&self) -> &[u8] {
+        &self.additional
+    }
+
+    // the payload before hashing
+    pub fn raw(&self) -> Vec<u8> {
+        [
+            self.call_data.as_slice(),
+            self.extra.as_slice(),
+            self.additional.as_slice(),
+        ]
+        .concat()
+    }
+
+    // the bytes the signer signs, payloads longer than 256 bytes are hashed first
+    pub fn encode(&self) -> Vec<u8> {
+        let bytes = self.raw();
+
+        if bytes.len() > 256 {
+            blake2_256(&bytes).to_vec()
+        } else {
+            bytes
+        }
+    }
+
+    // the bytes to sign as 0x prefixed hex
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.encode()))
+    }
+
+    // builds the UOS payload asking the owner of the public key to sign this
+    // extrinsic, as read by Parity Signer / Polkadot Vault. Use `uos_frames` to
+    // split it into QR codes. Like polkadot-js, the call is prefixed with its length
+    // in the frame, the signer strips it before signing.
+    pub fn to_uos(&self, k: KeyType, public: &[u8; 32]) -> Vec<u8> {
+        let crypto = match k {
+            KeyType::Ed25519 => UOS_ED25519,
+            KeyType::Sr25519 => UOS_SR25519,
+        };
+        // an immortal era is encoded as a single zero byte
+        let command = match self.extra.first() {
+            Some(0) => UOS_SIGN_IMMORTAL_TX,
+            _ => UOS_SIGN_TX,
+        };
+        // the additional signed data starts with the spec and transaction versions
+        // followed by the genesis hash
+        let genesis = self.additional.get(8..40).unwrap_or_default();
+        let call_len = Compact(self.call_data.len() as u32).encode();
+
+        [
+            &[UOS_SUBSTRATE, crypto, command][..],
+            public,
+            &call_len,
+            &self.call_data,
+            &self.extra,
+            &self.additional,
+            genesis,
+        ]
+        .concat()
+    }
+
+    // attaches a signature made by another party over `encode()` to the payload,
+    // failing if it was not made by the account with the given key type
+    pub fn into_signed(
+        self,
+        account: AccountId32,
+        k: KeyType,
+        signature: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if !verify(k, account.as_ref(), &self.encode(), signature) {
+            return Err(Error::Signer("signature does not match the payload".into()));
+        }
+
+        let signature: [u8; 64] = signature
+            .try_into()
+            .expect("verified signature is 64 bytes");
+
+        Ok(self.into_extrinsic(account, signer::signature(k, &signature)))
+    }
+
+    // encodes the signed extrinsic, ready to be submitted to a node
+    pub fn into_extrinsic(self, account: AccountId32, signature: MultiSignature) -> Vec<u8> {
+        let mut inner = Vec::new();
+        // signed extrinsic, version 4
+        (0b10000000 + 4u8).encode_to(&mut inner);
+        MultiAddress::<AccountId32, u32>::Id(account).encode_to(&mut inner);
+        signature.encode_to(&mut inner);
+        inner.extend_from_slice(&self.extra);
+        inner.extend_from_slice(&self.call_data);
+
+        let mut encoded = Compact(inner.len() as u32).encode();
+        encoded.extend(inner);
+        encoded
+    }
+}
+
+const UOS_SUBSTRATE: u8 = 0x53;
+const UOS_ED25519: u8 = 0x00;
+const UOS_SR25519: u8 = 0x01;
+const UOS_SIGN_TX: u8 = 0x00;
+const UOS_SIGN_IMMORTAL_TX: u8 = 0x02;
+const UOS_MULTIPART: u8 = 0x00;
+const UOS_FRAME_SIZE: usize = 1024;
+
+// splits an UOS payload into multipart frames, each one is shown as a binary QR code
+pub fn uos_frames(payload: &[u8]) -> Vec<Vec<u8>> {
+    let chunks: Vec<&[u8]> = payload.chunks(UOS_FRAME_SIZE).collect();
+    let count = chunks.len() as u16;
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            [
+                &[UOS_MULTIPART][..],
+                &count.to_be_bytes(),
+                &(index as u16).to_be_bytes(),
+                chunk,
+            ]
+            .concat()
+        })
+        .collect()
+}
+
+// EncodedCall is a call encoded against the node metadata, as returned by the
+// `*_call` methods of the client. It is submitted, estimated or dry run like any other call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedCall(Vec<u8>);
+
+impl EncodedCall {
+    pub fn new(call_data: Vec<u8>) -> Self {
+        EncodedCall(call_data)
+    }
+
+    pub fn call_data(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl TxPayload for EncodedCall {
+    fn encode_call_data_to(
+        &self,
+        _metadata: &Metadata,
+        out: &mut Vec<u8>,
+    ) -> Result<(), subxt::Error> {
+        out.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DispatchClass {
+    Normal,
+    Operational,
+    Mandatory,
+}
+
+// FeeEstimate is the fee an extrinsic would pay, as reported by `payment_queryInfo`
+#[derive(Debug, Clone)]
+pub struct FeeEstimate {
+    // the fee without the tip
+    pub partial_fee: u128,
+    // the execution weight of the call
+    pub weight: u64,
+    pub class: DispatchClass,
+}
+
+// the shape of the weight and fee changed between substrate versions, the weight was
+// a number before becoming `{refTime, proofSize}` and the fee can be a number or a string
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RuntimeDispatchInfo {
+    weight: serde_json::Value,
+    class: DispatchClass,
+    partial_fee: serde_json::Value,
+}
+
+impl TryFrom<RuntimeDispatchInfo> for FeeEstimate {
+    type Error = Error;
+
+    fn try_from(info: RuntimeDispatchInfo) -> Result<Self, Self::Error> {
+        let invalid = |what: &str, value: &serde_json::Value| {
+            Error::Decode(subxt::Error::Other(format!("invalid {}: {}", what, value)))
+        };
+
+        let weight = match &info.weight {
+            serde_json::Value::Object(weight) => weight.get("refTime").or(weight.get("ref_time")),
+            weight => Some(weight),
+        }
+        .and_then(json_number)
+        .ok_or_else(|| invalid("weight", &info.weight))?;
+
+        let partial_fee =
+            json_number(&info.partial_fee).ok_or_else(|| invalid("fee", &info.partial_fee))?;
+
+        Ok(FeeEstimate {
+            partial_fee,
+            weight: weight as u64,
+            class: info.class,
+        })
+    }
+}
+
+// numbers too large for JSON are sent as decimal or hex strings
+fn json_number(value: &serde_json::Value) -> Option<u128> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64().map(u128::from),
+        serde_json::Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+// DryRun is the outcome of applying an extrinsic on top of a block without
+// including it, as reported by `system_dryRun`
+#[derive(Debug)]
+pub enum DryRun {
+    // the extrinsic would succeed
+    Success,
+    // the extrinsic would be included in a block but its call would fail
+    Failed(Error),
+    // the extrinsic would be rejected, for example because of its nonce or because
+    // the account cannot pay the fee
+    Invalid(TransactionValidityError),
+}
+
+impl DryRun {
+    // decodes an `ApplyExtrinsicResult`, the dispatch error is decoded with the node
+    // metadata since its shape depends on the runtime
+    pub(crate) fn decode(bytes: &[u8], metadata: &Metadata) -> Result<Self, Error> {
+        match bytes.split_first() {
+            Some((0, [0])) => Ok(DryRun::Success),
+            Some((0, [1, err @ ..])) => Ok(DryRun::Failed(
+                DispatchError::decode_from(err, metadata).into(),
+            )),
+            Some((1, mut err)) => Ok(DryRun::Invalid(
+                TransactionValidityError::decode(&mut err).map_err(subxt::Error::from)?,
+            )),
+            _ => Err(Error::Decode(subxt::Error::Other(
+                "invalid dry run result".into(),
+            ))),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, DryRun::Success)
+    }
+}
+
+// Mortality sets for how long a signed extrinsic stays valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mortality {
+    // the extrinsic never expires
+    Immortal,
+    // the extrinsic is valid for `period` blocks starting at the checkpoint block,
+    // the period is rounded to a power of two between 4 and 65536
+    Mortal {
+        period: u64,
+        block_number: u64,
+        block_hash: Hash,
+    },
+}
+
+// OfflineBuilder builds and signs extrinsics without a connection to the node, for
+// example on an air-gapped machine. Everything the online client reads from the
+// node has to be provided: the metadata (as returned by `Client::metadata_bytes`),
+// the runtime version, the genesis hash and the nonce of the account. The signed
+// extrinsic is submitted later with `Client::submit_raw`.
+#[derive(Clone)]
+pub struct OfflineBuilder {
+    client: OfflineClient<PolkadotConfig>,
+    nonce: u32,
+    mortality: Mortality,
+    tip: u128,
+}
+
+impl OfflineBuilder {
+    pub fn new(
+        metadata: &[u8],
+        spec_version: u32,
+        transaction_version: u32,
+        genesis_hash: Hash,
+    ) -> Result<Self, Error> {
+        let metadata =
+            RuntimeMetadataPrefixed::decode(&mut &metadata[..]).map_err(subxt::Error::from)?;
+        let metadata = Metadata::try_from(metadata).map_err(subxt::Error::from)?;
+
+        let runtime = RuntimeVersion {
+            spec_version,
+            transaction_version,
+            other: Default::default(),
+        };
+
+        Ok(OfflineBuilder {
+            client: OfflineClient::new(genesis_hash, runtime, metadata),
+            nonce: 0,
+            mortality: Mortality::Immortal,
+            tip: 0,
+        })
+    }
+
+    // the nonce of the signing account, it must be the next unused nonce
+    pub fn nonce(mut self, nonce: u32) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    pub fn mortality(mut self, mortality: Mortality) -> Self {
+        self.mortality = mortality;
+        self
+    }
+
+    pub fn tip(mut self, tip: u128) -> Self {
+        self.tip = tip;
+        self
+    }
+
+    // builds the payload of the call that has to be signed
+    pub fn payload<Call: TxPayload>(&self, call: &Call) -> Result<SignerPayload, Error> {
+        self.client.tx().validate(call)?;
+        let call_data = self.client.tx().call_data(call)?;
+
+        let other = ExtrinsicParamsBuilderOf::new().tip(PlainTip::new(self.tip));
+        let other = match self.mortality {
+            Mortality::Immortal => other,
+            Mortality::Mortal {
+                period,
+                block_number,
+                block_hash,
+            } => other.era(Era::mortal(period, block_number), block_hash),
+        };
+
+        let runtime = self.client.runtime_version();
+        let params = ExtrinsicParamsOf::new(
+            runtime.spec_version,
+            runtime.transaction_version,
+            self.nonce,
+            self.client.genesis_hash(),
+            other,
+        );
+
+        Ok(SignerPayload::new(call_data, params))
+    }
+
+    // builds and signs the call, returning the encoded extrinsic
+    pub async fn sign<Call: TxPayload>(
+        &self,
+        call: &Call,
+        signer: &dyn TfchainSigner,
+    ) -> Result<Vec<u8>, Error> {
+        let payload = self.payload(call)?;
+        let signature = signer.sign(&payload.encode()).await?;
+
+        Ok(payload.into_extrinsic(signer.account_id(), signature))
+    }
+
+    // same as `sign` but the extrinsic is returned as 0x prefixed hex
+    pub async fn sign_hex<Call: TxPayload>(
+        &self,
+        call: &Call,
+        signer: &dyn TfchainSigner,
+    ) -> Result<String, Error> {
+        Ok(format!("0x{}", hex::encode(self.sign(call, signer).await?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtimes::testing;
+
+    const ALICE: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+    const GENESIS: &str = "91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3";
+    // Balances.transfer of 12345 to Bob
+    const CALL: &str = "0500008eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48e5c0";
+
+    fn signer_payload(extra: &str) -> SignerPayload {
+        // spec version 105, transaction version 1, genesis hash and checkpoint block hash
+        let additional = format!("6900000001000000{}{}", GENESIS, GENESIS);
+
+        SignerPayload::from_parts(
+            hex::decode(CALL).unwrap(),
+            hex::decode(extra).unwrap(),
+            hex::decode(additional).unwrap(),
+        )
+    }
+
+    // the frame polkadot-js builds with `createSignPayload` for the payload
+    #[test]
+    fn uos_payload() {
+        let public: [u8; 32] = hex::decode(ALICE).unwrap().try_into().unwrap();
+        // immortal era, nonce 0 and no tip
+        let payload = signer_payload("000000");
+
+        let expected = [
+            "530102",
+            ALICE,
+            // the call is prefixed with its compact encoded length, 37 bytes
+            "94",
+            CALL,
+            "000000",
+            "6900000001000000",
+            GENESIS,
+            GENESIS,
+            GENESIS,
+        ]
+        .concat();
+        assert_eq!(
+            hex::encode(payload.to_uos(KeyType::Sr25519, &public)),
+            expected
+        );
+
+        // the signed bytes do not include the length of the call
+        assert_eq!(
+            hex::encode(payload.encode()),
+            [CALL, "000000", "6900000001000000", GENESIS, GENESIS].concat()
+        );
+
+        // a mortal era asks to sign a mortal transaction, ed25519 keys use their own code
+        let mortal = signer_payload("b50300").to_uos(KeyType::Ed25519, &public);
+        assert_eq!(mortal[..3], [0x53, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn uos_multipart_frames() {
+        let payload = vec![7u8; UOS_FRAME_SIZE + 10];
+        let frames = uos_frames(&payload);
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0][..5], [0x00, 0x00, 0x02, 0x00, 0x00]);
+        assert_eq!(frames[0].len(), UOS_FRAME_SIZE + 5);
+        assert_eq!(frames[1][..5], [0x00, 0x00, 0x02, 0x00, 0x01]);
+        assert_eq!(frames[1][5..], [7u8; 10]);
+    }
+
+    #[test]
+    fn fee_estimate_from_dispatch_info() {
+        let estimate = |info: serde_json::Value| {
+            FeeEstimate::try_from(serde_json::from_value::<RuntimeDispatchInfo>(info).unwrap())
+        };
+
+        // before weights v2 the weight is a number and the fee a decimal string
+        let fee = estimate(serde_json::json!({
+            "weight": 195952000,
+            "class": "normal",
+            "partialFee": "155095146"
+        }))
+        .unwrap();
+        assert_eq!(fee.weight, 195952000);
+        assert_eq!(fee.partial_fee, 155095146);
+        assert_eq!(fee.class, DispatchClass::Normal);
+
+        let fee = estimate(serde_json::json!({
+            "weight": {"refTime": 195952000, "proofSize": 0},
+            "class": "operational",
+            "partialFee": "0xffffffffffffffffff"
+        }))
+        .unwrap();
+        assert_eq!(fee.weight, 195952000);
+        assert_eq!(fee.partial_fee, 0xffffffffffffffffff);
+        assert_eq!(fee.class, DispatchClass::Operational);
+
+        let fee = estimate(serde_json::json!({
+            "weight": {"ref_time": 7, "proof_size": 0},
+            "class": "mandatory",
+            "partialFee": 12
+        }))
+        .unwrap();
+        assert_eq!((fee.weight, fee.partial_fee), (7, 12));
+
+        for info in [
+            serde_json::json!({"weight": "heavy", "class": "normal", "partialFee": 1}),
+            serde_json::json!({"weight": {"proofSize": 0}, "class": "normal", "partialFee": 1}),
+            serde_json::json!({"weight": 1, "class": "normal", "partialFee": "-1"}),
+            serde_json::json!({"weight": 1, "class": "normal", "partialFee": null}),
+        ] {
+            assert!(matches!(estimate(info), Err(Error::Decode(_))));
+        }
+    }
+
+    #[test]
+    fn dry_run_outcomes() {
+        use crate::error::TfgridError;
+        use subxt::ext::sp_runtime::transaction_validity::InvalidTransaction;
+
+        let metadata = testing::metadata("devnet");
+
+        assert!(DryRun::decode(&[0, 0], &metadata).unwrap().is_success());
+
+        // DispatchError::Module of the tfgrid pallet
+        let pallet = metadata.pallet("TfgridModule").unwrap().index();
+        let error = (0..=u8::MAX)
+            .find(|i| {
+                metadata
+                    .error(pallet, *i)
+                    .is_ok_and(|e| e.error() == "TwinNotExists")
+            })
+            .unwrap();
+        let failed = DryRun::decode(&[0, 1, 3, pallet, error, 0, 0, 0], &metadata).unwrap();
+        assert!(matches!(
+            failed,
+            DryRun::Failed(Error::Tfgrid(TfgridError::TwinNotExists))
+        ));
+
+        // TransactionValidityError::Invalid(InvalidTransaction::Stale)
+        let invalid = DryRun::decode(&[1, 0, 3], &metadata).unwrap();
+        assert!(matches!(
+            invalid,
+            DryRun::Invalid(TransactionValidityError::Invalid(InvalidTransaction::Stale))
+        ));
+
+        for bytes in [&[][..], &[0], &[2, 0], &[1]] {
+            assert!(DryRun::decode(bytes, &metadata).is_err());
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use crate::client::Runtime;
+use crate::retry::RetryPolicy;
+use scale_info::TypeDef;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use subxt::{
+    ext::{
+        codec::Decode,
+        frame_metadata::{v14::RuntimeMetadataV14, RuntimeMetadata, RuntimeMetadataPrefixed},
+    },
+    metadata::Metadata,
+    OnlineClient, PolkadotConfig,
+};
+use tokio::{sync::broadcast, task::JoinHandle};
+
+// pallets the client reads storage from or submits calls to. a runtime upgrade
+// that changes any of them makes the generated code unsafe to use.
+const PALLETS: &[&str] = &["System", "Balances", "TfgridModule", "SmartContractModule"];
+
+// RuntimeUpgrade is emitted every time the node upgrades its runtime, it holds
+// the pallets and calls that no longer match the bundled metadata.
+#[derive(Debug, Clone)]
+pub struct RuntimeUpgrade {
+    pub spec_version: u32,
+    pub pallets: Vec<String>,
+    pub calls: Vec<String>,
+}
+
+impl RuntimeUpgrade {
+    // compares the hashes of the pallets and calls used by the client between
+    // the node metadata and the metadata bundled for the runtime. A runtime without
+    // bundled metadata works against the node metadata and is always compatible.
+    pub fn check(runtime: Runtime, spec_version: u32, node: &Metadata) -> Self {
+        let node = node.runtime_metadata();
+        let mut pallets = vec![];
+        let mut calls = vec![];
+
+        let bundled = match bundled_metadata(runtime) {
+            Some(bundled) => bundled,
+            None => {
+                return RuntimeUpgrade {
+                    spec_version,
+                    pallets,
+                    calls,
+                }
+            }
+        };
+        for name in PALLETS {
+            let bundled_pallet = bundled.pallets.iter().find(|p| &p.name == name);
+            let node_pallet = node.pallets.iter().find(|p| &p.name == name);
+
+            let (bundled_pallet, node_pallet) = match (bundled_pallet, node_pallet) {
+                (Some(b), Some(n)) => (b, n),
+                (None, None) => continue,
+                _ => {
+                    pallets.push(name.to_string());
+                    continue;
+                }
+            };
+
+            if subxt_metadata::get_pallet_hash(&bundled.types, bundled_pallet)
+                == subxt_metadata::get_pallet_hash(&node.types, node_pallet)
+            {
+                continue;
+            }
+            pallets.push(name.to_string());
+
+            for call in call_names(&bundled, name) {
+                let bundled_hash = subxt_metadata::get_call_hash(&bundled, name, &call);
+                let node_hash = subxt_metadata::get_call_hash(node, name, &call);
+                if bundled_hash.ok() != node_hash.ok() {
+                    calls.push(format!("{}::{}", name, call));
+                }
+            }
+        }
+
+        RuntimeUpgrade {
+            spec_version,
+            pallets,
+            calls,
+        }
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.pallets.is_empty()
+    }
+}
+
+fn bundled_metadata(runtime: Runtime) -> Option<RuntimeMetadataV14> {
+    let mut bytes = runtime.metadata()?;
+    let prefixed = RuntimeMetadataPrefixed::decode(&mut bytes).expect("bundled metadata is valid");
+
+    match prefixed.1 {
+        RuntimeMetadata::V14(metadata) => Some(metadata),
+        _ => panic!("bundled metadata is not v14"),
+    }
+}
+
+fn call_names(metadata: &RuntimeMetadataV14, pallet: &str) -> Vec<String> {
+    let calls = metadata
+        .pallets
+        .iter()
+        .find(|p| p.name == pallet)
+        .and_then(|p| p.calls.as_ref())
+        .and_then(|calls| metadata.types.resolve(calls.ty.id()));
+
+    match calls.map(|ty| ty.type_def()) {
+        Some(TypeDef::Variant(variant)) => variant
+            .variants()
+            .iter()
+            .map(|v| v.name().clone())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// Watcher keeps the client metadata up to date with the node runtime and
+// records the last upgrade. The subscription is re-established when it ends, while
+// it cannot be, the watcher records why so calls are not made unchecked. The
+// background task is stopped when the watcher is dropped.
+pub(crate) struct Watcher {
+    state: Arc<RwLock<WatchState>>,
+    events: broadcast::Sender<RuntimeUpgrade>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct WatchState {
+    last: Option<RuntimeUpgrade>,
+    // why the upgrades are not watched, set while the subscription is lost
+    lost: Option<String>,
+}
+
+impl Watcher {
+    pub(crate) fn new(api: OnlineClient<PolkadotConfig>, runtime: Runtime) -> Self {
+        let state = Arc::new(RwLock::new(WatchState::default()));
+        let (events, _) = broadcast::channel(16);
+
+        let task = tokio::spawn(watch(api, runtime, state.clone(), events.clone()));
+
+        Watcher {
+            state,
+            events,
+            task,
+        }
+    }
+
+    pub(crate) fn last(&self) -> Option<RuntimeUpgrade> {
+        self.state
+            .read()
+            .expect("lock is not poisoned")
+            .last
+            .clone()
+    }
+
+    // the reason the upgrades are not watched, `None` while they are
+    pub(crate) fn lost(&self) -> Option<String> {
+        self.state
+            .read()
+            .expect("lock is not poisoned")
+            .lost
+            .clone()
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<RuntimeUpgrade> {
+        self.events.subscribe()
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// backoff between the attempts to subscribe again to the runtime updates
+fn resubscribe_policy() -> RetryPolicy {
+    RetryPolicy {
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(60),
+        ..Default::default()
+    }
+}
+
+async fn watch(
+    api: OnlineClient<PolkadotConfig>,
+    runtime: Runtime,
+    state: Arc<RwLock<WatchState>>,
+    events: broadcast::Sender<RuntimeUpgrade>,
+) {
+    let policy = resubscribe_policy();
+    let mut failures = 0;
+
+    loop {
+        let (subscribed, reason) = follow(&api, runtime, &state, &events).await;
+        failures = if subscribed { 1 } else { failures + 1 };
+
+        // the subscription is first re-established right away, calls only fail once
+        // that did not work
+        if failures > 1 {
+            state.write().expect("lock is not poisoned").lost = Some(reason);
+            tokio::time::sleep(policy.backoff(failures - 1)).await;
+        }
+    }
+}
+
+// follows the runtime updates until the subscription fails, returning whether it
+// could subscribe and why it stopped
+async fn follow(
+    api: &OnlineClient<PolkadotConfig>,
+    runtime: Runtime,
+    state: &RwLock<WatchState>,
+    events: &broadcast::Sender<RuntimeUpgrade>,
+) -> (bool, String) {
+    let updater = api.subscribe_to_updates();
+    let mut updates = match updater.runtime_updates().await {
+        Ok(updates) => updates,
+        Err(err) => return (false, err.to_string()),
+    };
+
+    loop {
+        let update = match updates.next().await {
+            Some(Ok(update)) => update,
+            Some(Err(err)) => return (true, err.to_string()),
+            None => return (true, "runtime updates subscription ended".into()),
+        };
+
+        let upgrade = RuntimeUpgrade::check(
+            runtime,
+            update.runtime_version().spec_version,
+            update.metadata(),
+        );
+
+        // the subscription starts with the current version, which is not an upgrade.
+        // an upgrade missed while the subscription was lost is seen here first.
+        let applied = updater.apply_update(update).is_ok();
+
+        let mut state = state.write().expect("lock is not poisoned");
+        state.lost = None;
+        if !applied {
+            continue;
+        }
+
+        state.last = Some(upgrade.clone());
+        drop(state);
+        // sending only fails if there are no subscribers
+        let _ = events.send(upgrade);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Client;
+    use crate::error::Error;
+    use crate::runtimes::testing;
+    use subxt::{
+        error::RpcError,
+        rpc::{RawValue, RpcClientT, RpcFuture, RpcSubscription},
+    };
+
+    #[test]
+    fn bundled_metadata_is_compatible_with_itself() {
+        for (runtime, network) in [(Runtime::Devnet, "devnet"), (Runtime::Mainnet, "mainnet")] {
+            let upgrade = RuntimeUpgrade::check(runtime, 1, &testing::metadata(network));
+            assert!(upgrade.is_compatible(), "{:?}", upgrade);
+            assert!(upgrade.calls.is_empty(), "{:?}", upgrade);
+        }
+    }
+
+    #[test]
+    fn changed_pallets_are_incompatible() {
+        let upgrade = RuntimeUpgrade::check(Runtime::Devnet, 2, &testing::metadata("mainnet"));
+
+        assert!(!upgrade.is_compatible());
+        assert_eq!(upgrade.spec_version, 2);
+        assert!(upgrade.pallets.iter().any(|p| p == "TfgridModule"));
+        // the twin calls take an ip on mainnet and a relay on devnet
+        assert!(upgrade
+            .calls
+            .iter()
+            .any(|c| c == "TfgridModule::create_twin"));
+    }
+
+    #[test]
+    fn dynamic_runtime_is_always_compatible() {
+        let upgrade = RuntimeUpgrade::check(Runtime::Dynamic, 1, &testing::metadata("mainnet"));
+        assert!(upgrade.is_compatible());
+    }
+
+    // a node that answers the requests needed to build a client but refuses
+    // subscriptions
+    struct NoSubscriptions;
+
+    impl RpcClientT for NoSubscriptions {
+        fn request_raw<'a>(
+            &'a self,
+            method: &'a str,
+            _params: Option<Box<RawValue>>,
+        ) -> RpcFuture<'a, Box<RawValue>> {
+            Box::pin(async move {
+                let value = testing::client_response("devnet", method)
+                    .unwrap_or_else(|| panic!("unexpected request {}", method));
+                Ok(RawValue::from_string(value.to_string()).expect("value is json"))
+            })
+        }
+
+        fn subscribe_raw<'a>(
+            &'a self,
+            _sub: &'a str,
+            _params: Option<Box<RawValue>>,
+            _unsub: &'a str,
+        ) -> RpcFuture<'a, RpcSubscription> {
+            Box::pin(async { Err(RpcError::ClientError("subscriptions are refused".into())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn calls_fail_once_upgrades_are_not_watched() {
+        let client = Client::from_rpc_with_runtime(NoSubscriptions, Runtime::Devnet)
+            .await
+            .unwrap();
+
+        let err = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match client.ensure_compatible() {
+                    Ok(()) => tokio::time::sleep(Duration::from_millis(10)).await,
+                    Err(err) => return err,
+                }
+            }
+        })
+        .await
+        .expect("the watcher gives up subscribing");
+
+        assert!(
+            matches!(err, Error::UpgradesNotWatched(_)),
+            "unexpected error {}",
+            err
+        );
+        // calls are not built against a runtime that may have changed
+        assert!(matches!(
+            client.create_twin_call(Default::default()),
+            Err(Error::UpgradesNotWatched(_))
+        ));
+    }
+}
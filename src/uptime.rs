@@ -0,0 +1,237 @@
+use crate::client::{Client, UptimeReport};
+use crate::error::Error;
+use crate::signer::TfchainSigner;
+use crate::tx::TxOptions;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::task::JoinHandle;
+
+// UptimeReporterConfig sets how often the reporter reports. A failed report is
+// retried every `retry_interval` until one succeeds, then reports are back on
+// `interval`.
+#[derive(Debug, Clone)]
+pub struct UptimeReporterConfig {
+    pub interval: Duration,
+    pub retry_interval: Duration,
+    pub tx: TxOptions,
+}
+
+impl Default for UptimeReporterConfig {
+    // nodes are expected to report at least every hour
+    fn default() -> Self {
+        UptimeReporterConfig {
+            interval: Duration::from_secs(40 * 60),
+            retry_interval: Duration::from_secs(60),
+            tx: TxOptions::default(),
+        }
+    }
+}
+
+// ReporterStatus is what the reporter knows of its reports. `failures` counts the
+// attempts that failed since the last successful report and `last_error` is the
+// error of the last of them.
+#[derive(Debug, Clone, Default)]
+pub struct ReporterStatus {
+    pub last_report: Option<UptimeReport>,
+    pub last_reported_at: Option<SystemTime>,
+    pub failures: u32,
+    pub last_error: Option<String>,
+}
+
+// UptimeReporter reports the uptime of the node of the signer twin in the
+// background. It reports right away, then on the configured interval. The
+// background task is stopped when the reporter is dropped.
+pub struct UptimeReporter {
+    status: Arc<RwLock<ReporterStatus>>,
+    task: JoinHandle<()>,
+}
+
+impl UptimeReporter {
+    // Reports the time since the reporter was started as the uptime. Use
+    // `with_uptime` to report the uptime of the host instead.
+    pub fn start(
+        client: Client,
+        signer: Arc<dyn TfchainSigner>,
+        config: UptimeReporterConfig,
+    ) -> Self {
+        let started = Instant::now();
+
+        Self::with_uptime(client, signer, config, move || started.elapsed().as_secs())
+    }
+
+    // Reports the uptime returned by `uptime`, in seconds, which is called before
+    // every report.
+    pub fn with_uptime<F>(
+        client: Client,
+        signer: Arc<dyn TfchainSigner>,
+        config: UptimeReporterConfig,
+        uptime: F,
+    ) -> Self
+    where
+        F: Fn() -> u64 + Send + Sync + 'static,
+    {
+        let tx = config.tx.clone();
+        let submit = move |uptime| {
+            let (client, signer, tx) = (client.clone(), signer.clone(), tx.clone());
+            async move { client.report_uptime(signer.as_ref(), uptime, &tx).await }
+        };
+
+        Self::spawn(config, uptime, submit)
+    }
+
+    // runs the reports in the background, `submit` reports the uptime it is called with
+    fn spawn<F, S, Fut>(config: UptimeReporterConfig, uptime: F, submit: S) -> Self
+    where
+        F: Fn() -> u64 + Send + Sync + 'static,
+        S: Fn(u64) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<UptimeReport, Error>> + Send + 'static,
+    {
+        let status = Arc::new(RwLock::new(ReporterStatus::default()));
+
+        let task = tokio::spawn(report(config, uptime, submit, status.clone()));
+
+        UptimeReporter { status, task }
+    }
+
+    pub fn last_report(&self) -> Option<UptimeReport> {
+        self.status().last_report
+    }
+
+    pub fn status(&self) -> ReporterStatus {
+        self.status.read().expect("lock is not poisoned").clone()
+    }
+}
+
+impl Drop for UptimeReporter {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn report<F, S, Fut>(
+    config: UptimeReporterConfig,
+    uptime: F,
+    submit: S,
+    status: Arc<RwLock<ReporterStatus>>,
+) where
+    F: Fn() -> u64,
+    S: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<UptimeReport, Error>>,
+{
+    loop {
+        let result = submit(uptime()).await;
+
+        let delay = {
+            let mut status = status.write().expect("lock is not poisoned");
+            match result {
+                Ok(report) => {
+                    status.last_report = Some(report);
+                    status.last_reported_at = Some(SystemTime::now());
+                    status.failures = 0;
+                    status.last_error = None;
+                    config.interval
+                }
+                Err(err) => {
+                    status.failures += 1;
+                    status.last_error = Some(err.to_string());
+                    config.retry_interval
+                }
+            }
+        };
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    // a reporter whose reports fail while `failing` is set, `reports` counts the attempts
+    fn reporter(failing: Arc<AtomicBool>, reports: Arc<AtomicU32>) -> UptimeReporter {
+        let config = UptimeReporterConfig {
+            interval: Duration::from_secs(40 * 60),
+            retry_interval: Duration::from_secs(60),
+            tx: TxOptions::default(),
+        };
+
+        UptimeReporter::spawn(
+            config,
+            || 42,
+            move |uptime| {
+                let (failing, reports) = (failing.clone(), reports.clone());
+                async move {
+                    reports.fetch_add(1, Ordering::SeqCst);
+                    if failing.load(Ordering::SeqCst) {
+                        return Err(Error::Signer("node is down".into()));
+                    }
+                    Ok(UptimeReport {
+                        node_id: 1,
+                        timestamp: 1000,
+                        uptime,
+                    })
+                }
+            },
+        )
+    }
+
+    // lets the reporter run until it waits for its next report
+    async fn advance(by: Duration) {
+        tokio::time::advance(by).await;
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_reports_are_retried() {
+        let failing = Arc::new(AtomicBool::new(true));
+        let reports = Arc::new(AtomicU32::new(0));
+        let reporter = reporter(failing.clone(), reports.clone());
+
+        // reports right away
+        advance(Duration::ZERO).await;
+        assert_eq!(reports.load(Ordering::SeqCst), 1);
+        let status = reporter.status();
+        assert_eq!(status.failures, 1);
+        assert!(status.last_error.unwrap().contains("node is down"));
+        assert!(status.last_report.is_none());
+
+        // the failed report is retried after `retry_interval`, not before
+        advance(Duration::from_secs(59)).await;
+        assert_eq!(reports.load(Ordering::SeqCst), 1);
+        advance(Duration::from_secs(1)).await;
+        assert_eq!(reports.load(Ordering::SeqCst), 2);
+        assert_eq!(reporter.status().failures, 2);
+
+        // a successful report resets the failures
+        failing.store(false, Ordering::SeqCst);
+        advance(Duration::from_secs(60)).await;
+        assert_eq!(reports.load(Ordering::SeqCst), 3);
+        let status = reporter.status();
+        assert_eq!(status.failures, 0);
+        assert!(status.last_error.is_none());
+        assert_eq!(reporter.last_report().unwrap().uptime, 42);
+
+        // then the reports are back on `interval`
+        advance(Duration::from_secs(60)).await;
+        assert_eq!(reports.load(Ordering::SeqCst), 3);
+        advance(Duration::from_secs(39 * 60)).await;
+        assert_eq!(reports.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_stop_when_dropped() {
+        let reports = Arc::new(AtomicU32::new(0));
+        let reporter = reporter(Arc::new(AtomicBool::new(false)), reports.clone());
+
+        advance(Duration::ZERO).await;
+        assert_eq!(reports.load(Ordering::SeqCst), 1);
+
+        drop(reporter);
+        advance(Duration::from_secs(40 * 60)).await;
+        assert_eq!(reports.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::error::{Error, TfgridError};
+use crate::runtimes::types::FarmPublicIPInput;
+use std::collections::HashSet;
+use std::net::Ipv4Addr;
+
+// Input is checked before it is submitted so it fails early, with the error the
+// tfgrid pallet would have returned for it.
+
+const MIN_IP_LENGTH: usize = 9;
+const MAX_IP_LENGTH: usize = 18;
+const MIN_GATEWAY_LENGTH: usize = 7;
+const MAX_GATEWAY_LENGTH: usize = 15;
+
+// checks the public ips of a new farm, which must all be valid and distinct
+pub(crate) fn farm_public_ips(ips: &[FarmPublicIPInput]) -> Result<(), Error> {
+    let mut seen = HashSet::new();
+    for ip in ips {
+        farm_public_ip(&ip.ip, &ip.gateway)?;
+        if !seen.insert(ip.ip.as_str()) {
+            return Err(Error::Tfgrid(TfgridError::IpExists));
+        }
+    }
+
+    Ok(())
+}
+
+// checks that the ip is a public address in CIDR notation and that the gateway is
+// another address of its network
+pub(crate) fn farm_public_ip(ip: &str, gateway: &str) -> Result<(), Error> {
+    let (addr, prefix) = public_ip(ip)?;
+
+    if gateway.len() < MIN_GATEWAY_LENGTH {
+        return Err(Error::Tfgrid(TfgridError::GatewayIPTooShort));
+    }
+    if gateway.len() > MAX_GATEWAY_LENGTH {
+        return Err(Error::Tfgrid(TfgridError::GatewayIPTooLong));
+    }
+    let gateway: Ipv4Addr = gateway
+        .parse()
+        .map_err(|_| Error::Tfgrid(TfgridError::InvalidPublicIP))?;
+
+    let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+    if gateway == addr || u32::from(gateway) & mask != u32::from(addr) & mask {
+        return Err(Error::Tfgrid(TfgridError::InvalidPublicIP));
+    }
+
+    Ok(())
+}
+
+// parses a public ip in CIDR notation into its address and prefix length
+pub(crate) fn public_ip(ip: &str) -> Result<(Ipv4Addr, u32), Error> {
+    if ip.len() < MIN_IP_LENGTH {
+        return Err(Error::Tfgrid(TfgridError::PublicIPTooShort));
+    }
+    if ip.len() > MAX_IP_LENGTH {
+        return Err(Error::Tfgrid(TfgridError::PublicIPTooLong));
+    }
+
+    let invalid = || Error::Tfgrid(TfgridError::InvalidPublicIP);
+    let (addr, prefix) = ip.split_once('/').ok_or_else(invalid)?;
+    let addr: Ipv4Addr = addr.parse().map_err(|_| invalid())?;
+    let prefix: u32 = prefix
+        .parse()
+        .ok()
+        .filter(|prefix| *prefix <= 32)
+        .ok_or_else(invalid)?;
+
+    if addr.is_private()
+        || addr.is_loopback()
+        || addr.is_link_local()
+        || addr.is_unspecified()
+        || addr.is_broadcast()
+        || addr.is_multicast()
+        || addr.is_documentation()
+    {
+        return Err(invalid());
+    }
+
+    Ok((addr, prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tfgrid_error<T>(result: Result<T, Error>) -> TfgridError {
+        match result {
+            Err(Error::Tfgrid(err)) => err,
+            Err(err) => panic!("expected a tfgrid error, got {:?}", err),
+            Ok(_) => panic!("expected a tfgrid error"),
+        }
+    }
+
+    #[test]
+    fn public_ips() {
+        let (addr, prefix) = public_ip("185.206.122.33/24").unwrap();
+        assert_eq!(addr, Ipv4Addr::new(185, 206, 122, 33));
+        assert_eq!(prefix, 24);
+        assert_eq!(public_ip("185.206.122.33/32").unwrap().1, 32);
+
+        for ip in [
+            "10.0.0.10/24",
+            "172.16.5.4/16",
+            "192.168.1.10/24",
+            "127.0.0.1/8",
+            "169.254.1.1/16",
+            "0.0.0.0/0",
+            "255.255.255.255/32",
+            "224.0.0.1/24",
+            "203.0.113.5/24",
+            "185.206.122.33/33",
+            "185.206.122.33",
+            "185.206.122/24",
+        ] {
+            assert!(
+                matches!(tfgrid_error(public_ip(ip)), TfgridError::InvalidPublicIP),
+                "{}",
+                ip
+            );
+        }
+
+        assert!(matches!(
+            tfgrid_error(public_ip("1.1.1/24")),
+            TfgridError::PublicIPTooShort
+        ));
+        assert!(matches!(
+            tfgrid_error(public_ip("185.206.122.133/24 ")),
+            TfgridError::PublicIPTooLong
+        ));
+    }
+
+    #[test]
+    fn farm_public_ip_gateways() {
+        farm_public_ip("185.206.122.33/24", "185.206.122.1").unwrap();
+        farm_public_ip("185.206.122.33/16", "185.206.1.1").unwrap();
+
+        for (ip, gateway) in [
+            // outside of the subnet
+            ("185.206.122.33/24", "185.206.123.1"),
+            ("185.206.122.33/28", "185.206.122.1"),
+            // the gateway cannot be the ip itself
+            ("185.206.122.33/24", "185.206.122.33"),
+            // a /32 has no other address for the gateway
+            ("185.206.122.33/32", "185.206.122.1"),
+            ("185.206.122.33/32", "185.206.122.33"),
+            ("185.206.122.33/24", "not.an.ip.x"),
+            // the ip is checked first
+            ("192.168.1.10/24", "192.168.1.1"),
+        ] {
+            assert!(
+                matches!(
+                    tfgrid_error(farm_public_ip(ip, gateway)),
+                    TfgridError::InvalidPublicIP
+                ),
+                "{} {}",
+                ip,
+                gateway
+            );
+        }
+
+        assert!(matches!(
+            tfgrid_error(farm_public_ip("185.206.122.33/24", "1.1.1")),
+            TfgridError::GatewayIPTooShort
+        ));
+        assert!(matches!(
+            tfgrid_error(farm_public_ip("185.206.122.33/24", "185.206.122.100 ")),
+            TfgridError::GatewayIPTooLong
+        ));
+    }
+
+    #[test]
+    fn farm_public_ips_are_distinct() {
+        let input = |ip: &str, gateway: &str| FarmPublicIPInput {
+            ip: ip.into(),
+            gateway: gateway.into(),
+        };
+
+        farm_public_ips(&[
+            input("185.206.122.33/24", "185.206.122.1"),
+            input("185.206.122.34/24", "185.206.122.1"),
+        ])
+        .unwrap();
+
+        assert!(matches!(
+            tfgrid_error(farm_public_ips(&[
+                input("185.206.122.33/24", "185.206.122.1"),
+                input("185.206.122.33/24", "185.206.122.1"),
+            ])),
+            TfgridError::IpExists
+        ));
+    }
+}